@@ -0,0 +1,22 @@
+use std::env;
+use std::process::Command;
+
+fn main() {
+    // Expose the build environment to the `app_build_info` metric
+    let target = env::var("TARGET").unwrap_or_default();
+    let profile = env::var("PROFILE").unwrap_or_default();
+
+    let rustc = env::var("RUSTC").unwrap_or_else(|_| "rustc".to_string());
+    let rustc_version = Command::new(rustc)
+        .arg("--version")
+        .output()
+        .ok()
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|version| version.trim().to_string())
+        .unwrap_or_default();
+
+    println!("cargo:rustc-env=QDRANT_BUILD_TARGET={target}");
+    println!("cargo:rustc-env=QDRANT_BUILD_PROFILE={profile}");
+    println!("cargo:rustc-env=QDRANT_BUILD_RUSTC_VERSION={rustc_version}");
+    println!("cargo:rerun-if-changed=build.rs");
+}
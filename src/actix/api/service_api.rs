@@ -4,7 +4,7 @@ use std::sync::Arc;
 use actix_web::http::StatusCode;
 use actix_web::http::header::ContentType;
 use actix_web::rt::time::Instant;
-use actix_web::web::{Data, Query};
+use actix_web::web::Query;
 use actix_web::{HttpResponse, Responder, get, post, web};
 use common::types::{DetailsLevel, TelemetryDetail};
 use schemars::JsonSchema;
@@ -17,10 +17,9 @@ use tokio::sync::Mutex;
 use crate::actix::auth::ActixAccess;
 use crate::actix::helpers::{self, process_response_error};
 use crate::common::health;
-use crate::common::metrics::MetricsData;
+use crate::common::metrics::MetricsBuilder;
 use crate::common::stacktrace::get_stack_trace;
 use crate::common::telemetry::TelemetryCollector;
-use crate::settings::ServiceConfig;
 use crate::tracing;
 
 #[derive(Deserialize, Serialize, JsonSchema)]
@@ -58,13 +57,24 @@ fn telemetry(
 #[derive(Deserialize, Serialize, JsonSchema)]
 pub struct MetricsParam {
     pub anonymize: Option<bool>,
+    pub format: Option<MetricsFormat>,
+    pub family: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, JsonSchema, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricsFormat {
+    #[default]
+    Prometheus,
+    Ndjson,
+    Graphite,
 }
 
 #[get("/metrics")]
 async fn metrics(
     telemetry_collector: web::Data<Mutex<TelemetryCollector>>,
+    metrics_builder: web::Data<Mutex<MetricsBuilder>>,
     params: Query<MetricsParam>,
-    config: Data<ServiceConfig>,
     ActixAccess(access): ActixAccess,
 ) -> HttpResponse {
     if let Err(err) = access.check_global_access(AccessRequirements::new()) {
@@ -88,11 +98,36 @@ async fn metrics(
         telemetry_data
     };
 
-    let metrics_prefix = config.metrics_prefix.as_deref();
+    let metrics_data = {
+        let mut metrics_builder = metrics_builder.lock().await;
+        metrics_builder.update(&telemetry_data);
+        metrics_builder.snapshot()
+    };
 
-    HttpResponse::Ok()
-        .content_type(ContentType::plaintext())
-        .body(MetricsData::new_from_telemetry(telemetry_data, metrics_prefix).format_metrics())
+    if let Some(family) = &params.family {
+        return match metrics_data.format_family(family) {
+            Some(output) => HttpResponse::Ok()
+                .content_type(ContentType::plaintext())
+                .body(output),
+            None => process_response_error(
+                StorageError::not_found(format!("Metric family {family} not found")),
+                Instant::now(),
+                None,
+            ),
+        };
+    }
+
+    match params.format.unwrap_or_default() {
+        MetricsFormat::Prometheus => HttpResponse::Ok()
+            .content_type(ContentType::plaintext())
+            .body(metrics_data.format_metrics()),
+        MetricsFormat::Ndjson => HttpResponse::Ok()
+            .content_type("application/x-ndjson")
+            .body(metrics_data.to_ndjson()),
+        MetricsFormat::Graphite => HttpResponse::Ok()
+            .content_type(ContentType::plaintext())
+            .body(metrics_data.format_graphite(chrono::Utc::now().timestamp())),
+    }
 }
 
 #[get("/stacktrace")]
@@ -43,6 +43,7 @@ use crate::common::auth::AuthKeys;
 use crate::common::debugger::DebuggerState;
 use crate::common::health;
 use crate::common::http_client::HttpClient;
+use crate::common::metrics::MetricsBuilder;
 use crate::common::telemetry::TelemetryCollector;
 use crate::settings::{Settings, max_web_workers};
 use crate::tracing::LoggerHandle;
@@ -85,6 +86,13 @@ pub fn init(
         let health_checker = web::Data::new(health_checker);
         let web_ui_available = web_ui_folder(&settings);
         let service_config = web::Data::new(settings.service.clone());
+        let metrics_builder = web::Data::new(tokio::sync::Mutex::new(
+            MetricsBuilder::new(
+                settings.service.metrics_prefix.as_deref(),
+                &settings.service.metrics,
+            )
+            .with_storage_path(&settings.storage.storage_path),
+        ));
 
         let mut api_key_whitelist = vec![
             WhitelistItem::exact("/"),
@@ -144,6 +152,7 @@ pub fn init(
                 .app_data(TempFileConfig::default().directory(&upload_dir))
                 .app_data(MultipartFormConfig::default().total_limit(usize::MAX))
                 .app_data(service_config.clone())
+                .app_data(metrics_builder.clone())
                 .service(index)
                 .configure(config_collections_api)
                 .configure(config_snapshots_api)
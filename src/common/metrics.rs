@@ -1,11 +1,23 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::panic::{self, AssertUnwindSafe};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use std::{env, io};
 
 use api::rest::models::HardwareUsage;
+use collection::collection_manager::optimizers::TrackerStatus;
+use collection::operations::types::Datatype;
 use collection::shards::replica_set::ReplicaState;
+use collection::telemetry::CollectionTelemetry;
 use itertools::Itertools;
-use prometheus::TextEncoder;
 use prometheus::proto::{Counter, Gauge, LabelPair, Metric, MetricFamily, MetricType};
+use prometheus::{Encoder, TextEncoder};
+use regex::Regex;
 use segment::common::operation_time_statistics::OperationDurationStatistics;
+use segment::telemetry::SegmentTelemetry;
+use segment::types::QuantizationConfig;
 
 use super::telemetry_ops::hardware::HardwareTelemetry;
 use crate::common::telemetry::TelemetryData;
@@ -18,38 +30,60 @@ use crate::common::telemetry_ops::memory_telemetry::MemoryTelemetry;
 use crate::common::telemetry_ops::requests_telemetry::{
     GrpcTelemetry, RequestsTelemetry, WebApiTelemetry,
 };
+use crate::settings::{MetricsConfig, MetricsDurationUnit};
 
-/// Whitelist for REST endpoints in metrics output.
+/// Whitelist for REST endpoints in metrics output, along with their compact label.
 ///
 /// Contains selection of search, recommend, scroll and upsert endpoints.
 ///
 /// This array *must* be sorted.
-const REST_ENDPOINT_WHITELIST: &[&str] = &[
-    "/collections/{name}/index",
-    "/collections/{name}/points",
-    "/collections/{name}/points/batch",
-    "/collections/{name}/points/count",
-    "/collections/{name}/points/delete",
-    "/collections/{name}/points/discover",
-    "/collections/{name}/points/discover/batch",
-    "/collections/{name}/points/facet",
-    "/collections/{name}/points/payload",
-    "/collections/{name}/points/payload/clear",
-    "/collections/{name}/points/payload/delete",
-    "/collections/{name}/points/query",
-    "/collections/{name}/points/query/batch",
-    "/collections/{name}/points/query/groups",
-    "/collections/{name}/points/recommend",
-    "/collections/{name}/points/recommend/batch",
-    "/collections/{name}/points/recommend/groups",
-    "/collections/{name}/points/scroll",
-    "/collections/{name}/points/search",
-    "/collections/{name}/points/search/batch",
-    "/collections/{name}/points/search/groups",
-    "/collections/{name}/points/search/matrix/offsets",
-    "/collections/{name}/points/search/matrix/pairs",
-    "/collections/{name}/points/vectors",
-    "/collections/{name}/points/vectors/delete",
+const REST_ENDPOINT_WHITELIST: &[(&str, &str)] = &[
+    ("/collections/{name}/index", "index"),
+    ("/collections/{name}/points", "points"),
+    ("/collections/{name}/points/batch", "points_batch"),
+    ("/collections/{name}/points/count", "count"),
+    ("/collections/{name}/points/delete", "points_delete"),
+    ("/collections/{name}/points/discover", "discover"),
+    (
+        "/collections/{name}/points/discover/batch",
+        "discover_batch",
+    ),
+    ("/collections/{name}/points/facet", "facet"),
+    ("/collections/{name}/points/payload", "payload"),
+    ("/collections/{name}/points/payload/clear", "payload_clear"),
+    (
+        "/collections/{name}/points/payload/delete",
+        "payload_delete",
+    ),
+    ("/collections/{name}/points/query", "query"),
+    ("/collections/{name}/points/query/batch", "query_batch"),
+    ("/collections/{name}/points/query/groups", "query_groups"),
+    ("/collections/{name}/points/recommend", "recommend"),
+    (
+        "/collections/{name}/points/recommend/batch",
+        "recommend_batch",
+    ),
+    (
+        "/collections/{name}/points/recommend/groups",
+        "recommend_groups",
+    ),
+    ("/collections/{name}/points/scroll", "scroll"),
+    ("/collections/{name}/points/search", "search"),
+    ("/collections/{name}/points/search/batch", "search_batch"),
+    ("/collections/{name}/points/search/groups", "search_groups"),
+    (
+        "/collections/{name}/points/search/matrix/offsets",
+        "search_matrix_offsets",
+    ),
+    (
+        "/collections/{name}/points/search/matrix/pairs",
+        "search_matrix_pairs",
+    ),
+    ("/collections/{name}/points/vectors", "vectors"),
+    (
+        "/collections/{name}/points/vectors/delete",
+        "vectors_delete",
+    ),
 ];
 
 /// Whitelist for GRPC endpoints in metrics output.
@@ -83,59 +117,675 @@ const GRPC_ENDPOINT_WHITELIST: &[&str] = &[
     "/qdrant.Points/Upsert",
 ];
 
+/// Suffixes of gauge families that may be rounded to the configured gauge precision.
+const ROUNDED_GAUGE_SUFFIXES: &[&str] = &[
+    "_ratio",
+    "_utilization",
+    "_per_second",
+    "_duration_seconds",
+    "_duration_milliseconds",
+];
+
 /// For REST requests, only report timings when having this HTTP response status.
 const REST_TIMINGS_FOR_STATUS: u16 = 200;
 
 /// Encapsulates metrics data in Prometheus format.
+///
+/// Cheap to clone, clones share the metric families.
+#[derive(Debug, Default, Clone)]
 pub struct MetricsData {
-    metrics: Vec<MetricFamily>,
+    metrics: Arc<Vec<MetricFamily>>,
+    /// Global prefix the metric family names start with
+    prefix: Option<String>,
 }
 
 impl MetricsData {
+    /// Format metrics in Prometheus text format.
+    ///
+    /// Non-empty output always ends with exactly one newline, as required by strict scrapers,
+    /// independent of the behavior of the encoder.
     pub fn format_metrics(&self) -> String {
-        TextEncoder::new().encode_to_string(&self.metrics).unwrap()
+        let mut output = TextEncoder::new().encode_to_string(&self.metrics).unwrap();
+        output.truncate(output.trim_end_matches('\n').len());
+        if !output.is_empty() {
+            output.push('\n');
+        }
+        output
     }
 
-    /// Creates a new `MetricsData` from telemetry data and an optional prefix for metrics names.
-    pub fn new_from_telemetry(telemetry_data: TelemetryData, prefix: Option<&str>) -> Self {
-        let mut metrics = vec![];
-        telemetry_data.add_metrics(&mut metrics, prefix);
-        Self { metrics }
+    /// Sort metric families by name, and the series of each family by their labels.
+    fn sort(&mut self) {
+        let metrics = Arc::make_mut(&mut self.metrics);
+        metrics.sort_by(|a, b| a.name().cmp(b.name()));
+        for family in metrics {
+            family.mut_metric().sort_by_cached_key(|metric| {
+                metric
+                    .get_label()
+                    .iter()
+                    .map(|label| (label.name().to_string(), label.value().to_string()))
+                    .collect_vec()
+            });
+        }
+    }
+
+    /// Round the values of ratio, rate and duration gauges to the given number of significant
+    /// digits.
+    ///
+    /// Other gauges, such as sizes in bytes or timestamps, would lose their meaning when rounded.
+    fn round_gauges(&mut self, digits: u32) {
+        for family in Arc::make_mut(&mut self.metrics) {
+            if family.get_field_type() != MetricType::GAUGE
+                || !ROUNDED_GAUGE_SUFFIXES
+                    .iter()
+                    .any(|suffix| family.name().ends_with(suffix))
+            {
+                continue;
+            }
+            for metric in family.mut_metric() {
+                let mut gauge = Gauge::default();
+                gauge.set_value(round_significant(metric.get_gauge().get_value(), digits));
+                metric.set_gauge(gauge);
+            }
+        }
+    }
+
+    /// Get the metric family with the given name, including the prefix.
+    pub fn family(&self, name: &str) -> Option<&MetricFamily> {
+        self.metrics.iter().find(|family| family.name() == name)
+    }
+
+    /// Format only the metric family with the given name in Prometheus text format.
+    pub fn format_family(&self, name: &str) -> Option<String> {
+        let family = self.family(name)?;
+        Some(
+            TextEncoder::new()
+                .encode_to_string(std::slice::from_ref(family))
+                .unwrap(),
+        )
+    }
+
+    /// Write metrics in Prometheus text format to the given file, as expected by the node exporter
+    /// textfile collector.
+    ///
+    /// Metrics are written to a temporary file next to it first, which is then renamed to replace
+    /// the file atomically, so that the collector never reads a partially written file.
+    pub fn write_textfile(&self, path: &Path) -> io::Result<()> {
+        let mut tmp_path = path.as_os_str().to_owned();
+        tmp_path.push(".tmp");
+        fs_err::write(&tmp_path, self.format_metrics())?;
+        fs_err::rename(&tmp_path, path)
+    }
+
+    /// Size of the metrics in Prometheus text format, without encoding them into a string.
+    pub fn encoded_size(&self) -> usize {
+        let mut writer = CountingWriter::default();
+        TextEncoder::new()
+            .encode(&self.metrics, &mut writer)
+            .unwrap();
+        writer.bytes
+    }
+
+    /// Format metrics as newline-delimited JSON, with one object per sample.
+    ///
+    /// Histograms and summaries are expanded into a sample per bucket or quantile, along with their
+    /// sum and count. Quantiles of summaries without samples are NaN and therefore skipped.
+    pub fn to_ndjson(&self) -> String {
+        let mut output = String::new();
+        for family in self.metrics.iter() {
+            let name = family.name();
+            let r#type = match family.get_field_type() {
+                MetricType::COUNTER => "counter",
+                MetricType::GAUGE => "gauge",
+                MetricType::SUMMARY => "summary",
+                MetricType::UNTYPED => "untyped",
+                MetricType::HISTOGRAM => "histogram",
+            };
+
+            for metric in family.get_metric() {
+                let labels: serde_json::Map<String, serde_json::Value> = metric
+                    .get_label()
+                    .iter()
+                    .map(|label| {
+                        (
+                            label.name().to_string(),
+                            serde_json::Value::from(label.value()),
+                        )
+                    })
+                    .collect();
+                let mut push_sample = |name: &str, labels: serde_json::Map<_, _>, value: f64| {
+                    let sample = serde_json::json!({
+                        "name": name,
+                        "labels": labels,
+                        "value": value,
+                        "type": r#type,
+                    });
+                    output.push_str(&sample.to_string());
+                    output.push('\n');
+                };
+
+                match family.get_field_type() {
+                    MetricType::COUNTER => {
+                        push_sample(name, labels, metric.get_counter().get_value());
+                    }
+                    MetricType::GAUGE => {
+                        push_sample(name, labels, metric.get_gauge().get_value());
+                    }
+                    MetricType::HISTOGRAM => {
+                        let histogram = metric.get_histogram();
+                        let bucket_name = format!("{name}_bucket");
+                        let upper_bounds = histogram
+                            .get_bucket()
+                            .iter()
+                            .map(|bucket| {
+                                (bucket.upper_bound().to_string(), bucket.cumulative_count())
+                            })
+                            .chain([("+Inf".to_string(), histogram.get_sample_count())]);
+                        for (upper_bound, cumulative_count) in upper_bounds {
+                            let mut bucket_labels = labels.clone();
+                            bucket_labels
+                                .insert("le".to_string(), serde_json::Value::from(upper_bound));
+                            push_sample(&bucket_name, bucket_labels, cumulative_count as f64);
+                        }
+                        push_sample(
+                            &format!("{name}_sum"),
+                            labels.clone(),
+                            histogram.get_sample_sum(),
+                        );
+                        push_sample(
+                            &format!("{name}_count"),
+                            labels,
+                            histogram.get_sample_count() as f64,
+                        );
+                    }
+                    MetricType::SUMMARY => {
+                        let summary = metric.get_summary();
+                        // Quantiles are NaN without samples, which JSON numbers can't represent
+                        for quantile in summary
+                            .get_quantile()
+                            .iter()
+                            .filter(|quantile| !quantile.value().is_nan())
+                        {
+                            let mut quantile_labels = labels.clone();
+                            quantile_labels.insert(
+                                "quantile".to_string(),
+                                serde_json::Value::from(quantile.quantile().to_string()),
+                            );
+                            push_sample(name, quantile_labels, quantile.value());
+                        }
+                        push_sample(
+                            &format!("{name}_sum"),
+                            labels.clone(),
+                            summary.get_sample_sum(),
+                        );
+                        push_sample(
+                            &format!("{name}_count"),
+                            labels,
+                            summary.get_sample_count() as f64,
+                        );
+                    }
+                    // Not emitted by any metrics provider
+                    MetricType::UNTYPED => {}
+                }
+            }
+        }
+        output
+    }
+
+    /// Format metrics in the tagged Graphite plaintext format, with one line per sample at the
+    /// given unix timestamp in seconds.
+    ///
+    /// Paths are dot-joined from the global prefix the metrics were built with, the family name and
+    /// the kind of histogram or summary series, such as `qdrant.rest_responses_duration_seconds.bucket`. Labels are
+    /// flattened into tags. Histograms and summaries are expanded into a series per bucket or
+    /// quantile, along with their sum and count. Quantiles of summaries without samples are NaN
+    /// and therefore skipped.
+    pub fn format_graphite(&self, timestamp: i64) -> String {
+        let mut output = String::new();
+        for family in self.metrics.iter() {
+            let name = &graphite_path(self.prefix.as_deref(), family.name());
+            for metric in family.get_metric() {
+                let tags: String = metric
+                    .get_label()
+                    .iter()
+                    .filter_map(|label| graphite_tag(label.name(), label.value()))
+                    .collect();
+                let mut push_sample = |path: &str, extra_tags: &str, value: f64| {
+                    output.push_str(&format!("{path}{tags}{extra_tags} {value} {timestamp}\n"));
+                };
+
+                match family.get_field_type() {
+                    MetricType::COUNTER => {
+                        push_sample(name, "", metric.get_counter().get_value());
+                    }
+                    MetricType::GAUGE => {
+                        push_sample(name, "", metric.get_gauge().get_value());
+                    }
+                    MetricType::HISTOGRAM => {
+                        let histogram = metric.get_histogram();
+                        let bucket_path = format!("{name}.bucket");
+                        let upper_bounds = histogram
+                            .get_bucket()
+                            .iter()
+                            .map(|bucket| {
+                                (bucket.upper_bound().to_string(), bucket.cumulative_count())
+                            })
+                            .chain([("+Inf".to_string(), histogram.get_sample_count())]);
+                        for (upper_bound, cumulative_count) in upper_bounds {
+                            push_sample(
+                                &bucket_path,
+                                &format!(";le={upper_bound}"),
+                                cumulative_count as f64,
+                            );
+                        }
+                        push_sample(&format!("{name}.sum"), "", histogram.get_sample_sum());
+                        push_sample(
+                            &format!("{name}.count"),
+                            "",
+                            histogram.get_sample_count() as f64,
+                        );
+                    }
+                    MetricType::SUMMARY => {
+                        let summary = metric.get_summary();
+                        for quantile in summary
+                            .get_quantile()
+                            .iter()
+                            .filter(|quantile| !quantile.value().is_nan())
+                        {
+                            push_sample(
+                                name,
+                                &format!(";quantile={}", quantile.quantile()),
+                                quantile.value(),
+                            );
+                        }
+                        push_sample(&format!("{name}.sum"), "", summary.get_sample_sum());
+                        push_sample(
+                            &format!("{name}.count"),
+                            "",
+                            summary.get_sample_count() as f64,
+                        );
+                    }
+                    // Not emitted by any metrics provider
+                    MetricType::UNTYPED => {}
+                }
+            }
+        }
+        output
+    }
+
+    /// Sorted names of all emitted metric families.
+    #[cfg(test)]
+    pub fn family_names(&self) -> Vec<String> {
+        self.metrics
+            .iter()
+            .map(|family| family.name().to_string())
+            .sorted()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+impl From<Vec<MetricFamily>> for MetricsData {
+    fn from(metrics: Vec<MetricFamily>) -> Self {
+        Self {
+            metrics: Arc::new(metrics),
+            prefix: None,
+        }
+    }
+}
+
+/// Reusable builder for [`MetricsData`].
+///
+/// Keeps the list of metric families between updates, so that repeated scrapes reuse its
+/// allocation. The families themselves are rebuilt by the metrics providers on every update.
+/// Snapshots share the collected metrics instead of copying them.
+#[derive(Debug, Default)]
+pub struct MetricsBuilder {
+    data: MetricsData,
+    prefix: Option<String>,
+    config: MetricsConfig,
+    /// Compiled [`MetricsConfig::collection_group_pattern`]
+    collection_group_regex: Option<Regex>,
+    /// Labels attached to all metrics, resolved from environment variables
+    const_labels: Vec<(String, String)>,
+    #[cfg(target_os = "linux")]
+    procfs: ProcFsCache,
+    response_rates: ResponseRates,
+    /// Path of the storage volume to report disk space of
+    storage_path: Option<PathBuf>,
+}
+
+impl MetricsBuilder {
+    /// Creates a new `MetricsBuilder` with an optional prefix for metrics names and the metrics
+    /// configuration.
+    pub fn new(prefix: Option<&str>, config: &MetricsConfig) -> Self {
+        let const_labels = config
+            .env_labels
+            .iter()
+            .filter_map(|(label, var)| match env::var(var) {
+                Ok(value) => Some((label.clone(), value)),
+                Err(err) => {
+                    log::debug!("Skipping metrics label {label}, can't read {var}: {err}");
+                    None
+                }
+            })
+            .sorted()
+            .collect();
+
+        // Compile the pattern once, it is validated when loading the settings
+        let collection_group_regex =
+            config
+                .collection_group_pattern
+                .as_deref()
+                .and_then(|pattern| {
+                    Regex::new(pattern)
+                        .map_err(|err| {
+                            log::warn!("Invalid collection group pattern {pattern:?}: {err}")
+                        })
+                        .ok()
+                });
+
+        Self {
+            data: MetricsData {
+                prefix: prefix.map(str::to_string),
+                ..Default::default()
+            },
+            prefix: prefix.map(str::to_string),
+            config: config.clone(),
+            collection_group_regex,
+            const_labels,
+            #[cfg(target_os = "linux")]
+            procfs: ProcFsCache::new(config.procfs_scrape_interval),
+            response_rates: ResponseRates::new(
+                config
+                    .response_rates_window_sec
+                    .map_or(DEFAULT_RESPONSE_RATES_WINDOW, Duration::from_secs),
+            ),
+            storage_path: None,
+        }
+    }
+
+    /// Also report free and total disk space of the volume containing the given storage path.
+    pub fn with_storage_path(mut self, storage_path: impl Into<PathBuf>) -> Self {
+        self.storage_path = Some(storage_path.into());
+        self
+    }
+
+    /// Replace the collected metrics with the ones of the given telemetry data.
+    pub fn update(&mut self, telemetry_data: &TelemetryData) {
+        // Cheap to clone, the compiled regex is shared
+        let collection_group_regex = self.collection_group_regex.clone();
+        let telemetry = TelemetryMetrics {
+            telemetry: telemetry_data,
+            collection_group_regex: collection_group_regex.as_ref(),
+        };
+
+        #[cfg(target_os = "linux")]
+        {
+            let procfs = self.procfs.get();
+            self.update_from(&WithProcFs {
+                provider: &telemetry,
+                procfs,
+                cgroup: CgroupMetrics::collect(),
+            });
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        self.update_from(&telemetry);
+    }
+
+    fn update_from(&mut self, provider: &impl MetricsProvider) {
+        self.update_from_at(provider, Instant::now());
+    }
+
+    fn update_from_at(&mut self, provider: &impl MetricsProvider, now: Instant) {
+        // Metrics of a snapshot still in use must not change, start over instead of copying them
+        if Arc::get_mut(&mut self.data.metrics).is_none() {
+            self.data = MetricsData {
+                prefix: self.prefix.clone(),
+                ..Default::default()
+            };
+        }
+        let data = &mut self.data;
+        let metrics = Arc::get_mut(&mut data.metrics).expect("metrics data is not shared");
+
+        metrics.clear();
+        provider.add_metrics(metrics, self.prefix.as_deref(), &self.config);
+        if self.config.response_rates {
+            self.response_rates.add_rates(metrics, now);
+        }
+        if let Some(storage_path) = &self.storage_path {
+            match StorageDiskMetrics::collect(storage_path) {
+                Ok(disk_metrics) => add_metrics_catch_unwind(
+                    &disk_metrics,
+                    metrics,
+                    self.prefix.as_deref(),
+                    &self.config,
+                ),
+                Err(err) => log::warn!("Error reading disk space of storage: {err}"),
+            }
+        }
+        // After all providers, so that failures of this scrape are included
+        metrics.push(metric_family(
+            "metrics_collection_errors_total",
+            "total number of metrics providers that failed while collecting metrics",
+            MetricType::COUNTER,
+            vec![counter(
+                METRICS_COLLECTION_ERRORS.load(Ordering::Relaxed) as f64,
+                &[],
+            )],
+            self.prefix.as_deref(),
+        ));
+        add_const_labels(metrics, &self.const_labels);
+        if let Some(digits) = self.config.gauge_precision {
+            data.round_gauges(digits);
+        }
+
+        if self.config.payload_size {
+            // Size of all other metrics, as the payload size can't include itself
+            let payload_bytes = data.encoded_size();
+            let labels = self
+                .const_labels
+                .iter()
+                .map(|(name, value)| (name.as_str(), value.as_str()))
+                .collect_vec();
+            Arc::make_mut(&mut data.metrics).push(metric_family(
+                "metrics_payload_bytes",
+                "size of all other metrics in Prometheus text format",
+                MetricType::GAUGE,
+                vec![gauge(payload_bytes as f64, &labels)],
+                self.prefix.as_deref(),
+            ));
+        }
+
+        if self.config.sort_output {
+            data.sort();
+        }
+    }
+
+    /// Get the currently collected metrics, without copying them.
+    pub fn snapshot(&self) -> MetricsData {
+        self.data.clone()
+    }
+}
+
+/// Window to compute response rates over, if not configured.
+const DEFAULT_RESPONSE_RATES_WINDOW: Duration = Duration::from_secs(60);
+
+/// Response counters of previous scrapes, to derive response rates from.
+///
+/// Rates are computed over a fixed window rather than since the previous scrape, so that they
+/// don't depend on which of multiple scrapers scraped last.
+#[derive(Debug, Default)]
+struct ResponseRates {
+    window: Duration,
+    /// Counter values by family name and labels of previous scrapes, oldest first
+    samples: VecDeque<(Instant, HashMap<(String, Vec<(String, String)>), f64>)>,
+}
+
+impl ResponseRates {
+    fn new(window: Duration) -> Self {
+        Self {
+            window,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Add a `responses_per_second` gauge for every `responses_total` counter, based on its
+    /// increase over the rate window.
+    fn add_rates(&mut self, metrics: &mut Vec<MetricFamily>, now: Instant) {
+        // Keep the newest sample at least as old as the window as baseline, so that rates cover
+        // the whole window once enough scrapes happened
+        while self
+            .samples
+            .get(1)
+            .is_some_and(|(at, _)| now.saturating_duration_since(*at) >= self.window)
+        {
+            self.samples.pop_front();
+        }
+        let baseline = self
+            .samples
+            .front()
+            .map(|(at, totals)| (now.saturating_duration_since(*at).as_secs_f64(), totals));
+
+        let mut totals = HashMap::new();
+        let mut rate_families = vec![];
+        for family in metrics.iter() {
+            let Some(family_prefix) = family.name().strip_suffix("responses_total") else {
+                continue;
+            };
+
+            let mut rates = vec![];
+            for metric in family.get_metric() {
+                let labels = metric
+                    .get_label()
+                    .iter()
+                    .map(|label| (label.name().to_string(), label.value().to_string()))
+                    .collect_vec();
+                let total = metric.get_counter().get_value();
+                let key = (family.name().to_string(), labels);
+
+                if let Some((elapsed, previous_totals)) = baseline
+                    && elapsed > 0.0
+                    && let Some(&previous) = previous_totals.get(&key)
+                {
+                    // Counters restart from zero, for example when telemetry is reset
+                    let increase = if total >= previous {
+                        total - previous
+                    } else {
+                        total
+                    };
+                    let labels = key
+                        .1
+                        .iter()
+                        .map(|(name, value)| (name.as_str(), value.as_str()))
+                        .collect_vec();
+                    rates.push(gauge(increase / elapsed, &labels));
+                }
+                totals.insert(key, total);
+            }
+
+            if !rates.is_empty() {
+                // Prefix is already part of the counter family name
+                rate_families.push(metric_family(
+                    &format!("{family_prefix}responses_per_second"),
+                    "rate of responses per second over the rate window",
+                    MetricType::GAUGE,
+                    rates,
+                    None,
+                ));
+            }
+        }
+
+        self.samples.push_back((now, totals));
+        metrics.extend(rate_families);
     }
 }
 
 trait MetricsProvider {
     /// Add metrics definitions for this.
-    fn add_metrics(&self, metrics: &mut Vec<MetricFamily>, prefix: Option<&str>);
+    fn add_metrics(
+        &self,
+        metrics: &mut Vec<MetricFamily>,
+        prefix: Option<&str>,
+        config: &MetricsConfig,
+    );
+}
+
+/// Telemetry data along with the compiled collection group pattern of the metrics builder.
+struct TelemetryMetrics<'a> {
+    telemetry: &'a TelemetryData,
+    collection_group_regex: Option<&'a Regex>,
 }
 
-impl MetricsProvider for TelemetryData {
-    fn add_metrics(&self, metrics: &mut Vec<MetricFamily>, prefix: Option<&str>) {
-        self.app.add_metrics(metrics, prefix);
-        self.collections.add_metrics(metrics, prefix);
-        if let Some(cluster) = &self.cluster {
-            cluster.add_metrics(metrics, prefix);
+impl MetricsProvider for TelemetryMetrics<'_> {
+    fn add_metrics(
+        &self,
+        metrics: &mut Vec<MetricFamily>,
+        prefix: Option<&str>,
+        config: &MetricsConfig,
+    ) {
+        let telemetry = self.telemetry;
+        let collections = CollectionsMetrics {
+            telemetry: &telemetry.collections,
+            group_regex: self.collection_group_regex,
+        };
+
+        add_metrics_catch_unwind(&telemetry.app, metrics, prefix, config);
+        add_metrics_catch_unwind(&collections, metrics, prefix, config);
+        if let Some(cluster) = &telemetry.cluster {
+            add_metrics_catch_unwind(cluster, metrics, prefix, config);
         }
-        if let Some(requests) = &self.requests {
-            requests.add_metrics(metrics, prefix);
+        if let Some(requests) = &telemetry.requests {
+            add_metrics_catch_unwind(requests, metrics, prefix, config);
         }
-        if let Some(hardware) = &self.hardware {
-            hardware.add_metrics(metrics, prefix);
+        if let Some(hardware) = &telemetry.hardware {
+            add_metrics_catch_unwind(hardware, metrics, prefix, config);
         }
-        if let Some(mem) = &self.memory {
-            mem.add_metrics(metrics, prefix);
+        if let Some(mem) = &telemetry.memory {
+            add_metrics_catch_unwind(mem, metrics, prefix, config);
         }
+    }
+}
 
-        #[cfg(target_os = "linux")]
-        match ProcFsMetrics::collect() {
-            Ok(procfs_provider) => procfs_provider.add_metrics(metrics, prefix),
-            Err(err) => log::warn!("Error reading procfs infos: {err:?}"),
-        };
+/// Number of metrics providers that panicked while collecting metrics.
+static METRICS_COLLECTION_ERRORS: AtomicUsize = AtomicUsize::new(0);
+
+/// Add metrics of the given provider.
+///
+/// A panicking provider is logged and counted in [`METRICS_COLLECTION_ERRORS`], leaving out its
+/// metrics without aborting the collection of all others.
+fn add_metrics_catch_unwind(
+    provider: &impl MetricsProvider,
+    metrics: &mut Vec<MetricFamily>,
+    prefix: Option<&str>,
+    config: &MetricsConfig,
+) {
+    let len = metrics.len();
+    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+        provider.add_metrics(metrics, prefix, config);
+    }));
+
+    match result {
+        Ok(()) => {}
+        Err(err) => {
+            // Drop metrics the provider added before panicking
+            metrics.truncate(len);
+            METRICS_COLLECTION_ERRORS.fetch_add(1, Ordering::Relaxed);
+
+            let message = common::panic::downcast_str(&err).unwrap_or("");
+            let separator = if !message.is_empty() { ": " } else { "" };
+            log::error!("Metrics provider panicked, skipping its metrics{separator}{message}");
+        }
     }
 }
 
 impl MetricsProvider for AppBuildTelemetry {
-    fn add_metrics(&self, metrics: &mut Vec<MetricFamily>, prefix: Option<&str>) {
+    fn add_metrics(
+        &self,
+        metrics: &mut Vec<MetricFamily>,
+        prefix: Option<&str>,
+        config: &MetricsConfig,
+    ) {
         metrics.push(metric_family(
             "app_info",
             "information about qdrant server",
@@ -146,26 +796,134 @@ impl MetricsProvider for AppBuildTelemetry {
             )],
             prefix,
         ));
+        metrics.push(metric_family(
+            "app_build_info",
+            "information about the build of qdrant server",
+            MetricType::GAUGE,
+            vec![gauge(
+                1.0,
+                &[
+                    ("rustc_version", env!("QDRANT_BUILD_RUSTC_VERSION")),
+                    ("target", env!("QDRANT_BUILD_TARGET")),
+                    ("profile", env!("QDRANT_BUILD_PROFILE")),
+                ],
+            )],
+            prefix,
+        ));
+        metrics.push(log_level_family(log::max_level(), prefix));
+        if config.restart_epoch {
+            metrics.push(metric_family(
+                "process_restart_epoch",
+                "start time of the process in seconds since the epoch, changes on restart",
+                MetricType::GAUGE,
+                vec![gauge(self.startup.timestamp() as f64, &[])],
+                prefix,
+            ));
+        }
         self.features
             .iter()
-            .for_each(|f| f.add_metrics(metrics, prefix));
+            .for_each(|f| f.add_metrics(metrics, prefix, config));
     }
 }
 
+/// Info metric for the effective log level, which may be changed at runtime.
+fn log_level_family(level: log::LevelFilter, prefix: Option<&str>) -> MetricFamily {
+    metric_family(
+        "app_log_level",
+        "effective log level of qdrant server",
+        MetricType::GAUGE,
+        vec![gauge(
+            1.0,
+            &[("level", &level.as_str().to_ascii_lowercase())],
+        )],
+        prefix,
+    )
+}
+
 impl MetricsProvider for AppFeaturesTelemetry {
-    fn add_metrics(&self, metrics: &mut Vec<MetricFamily>, prefix: Option<&str>) {
+    fn add_metrics(
+        &self,
+        metrics: &mut Vec<MetricFamily>,
+        prefix: Option<&str>,
+        _config: &MetricsConfig,
+    ) {
         metrics.push(metric_family(
             "app_status_recovery_mode",
             "features enabled in qdrant server",
             MetricType::GAUGE,
             vec![gauge(if self.recovery_mode { 1.0 } else { 0.0 }, &[])],
             prefix,
-        ))
+        ));
+
+        let AppFeaturesTelemetry {
+            debug,
+            service_debug_feature,
+            recovery_mode,
+            gpu,
+            rocksdb,
+        } = *self;
+        let features = [
+            ("debug", debug),
+            ("service_debug_feature", service_debug_feature),
+            ("recovery_mode", recovery_mode),
+            ("gpu", gpu),
+            ("rocksdb", rocksdb),
+        ];
+        metrics.push(metric_family(
+            "app_feature_enabled",
+            "whether a feature is enabled in qdrant server",
+            MetricType::GAUGE,
+            features
+                .into_iter()
+                .map(|(feature, enabled)| {
+                    gauge(if enabled { 1.0 } else { 0.0 }, &[("feature", feature)])
+                })
+                .collect(),
+            prefix,
+        ));
+    }
+}
+
+/// Collections telemetry along with the compiled
+/// [`MetricsConfig::collection_group_pattern`], to report points per group of collections.
+struct CollectionsMetrics<'a> {
+    telemetry: &'a CollectionsTelemetry,
+    group_regex: Option<&'a Regex>,
+}
+
+impl MetricsProvider for CollectionsMetrics<'_> {
+    fn add_metrics(
+        &self,
+        metrics: &mut Vec<MetricFamily>,
+        prefix: Option<&str>,
+        config: &MetricsConfig,
+    ) {
+        self.telemetry
+            .add_metrics_grouped(metrics, prefix, config, self.group_regex);
     }
 }
 
 impl MetricsProvider for CollectionsTelemetry {
-    fn add_metrics(&self, metrics: &mut Vec<MetricFamily>, prefix: Option<&str>) {
+    fn add_metrics(
+        &self,
+        metrics: &mut Vec<MetricFamily>,
+        prefix: Option<&str>,
+        config: &MetricsConfig,
+    ) {
+        self.add_metrics_grouped(metrics, prefix, config, None);
+    }
+}
+
+impl CollectionsTelemetry {
+    /// Add metrics of all collections, also reporting points per group of collections if a group
+    /// regex is given.
+    fn add_metrics_grouped(
+        &self,
+        metrics: &mut Vec<MetricFamily>,
+        prefix: Option<&str>,
+        config: &MetricsConfig,
+        group_regex: Option<&Regex>,
+    ) {
         metrics.push(metric_family(
             "collections_total",
             "number of collections",
@@ -184,6 +942,19 @@ impl MetricsProvider for CollectionsTelemetry {
         // Points per collection
         let mut points_per_collection = vec![];
 
+        // Shard transfers in progress over all collections
+        let mut active_transfers = 0;
+
+        // Points and vectors over all collections
+        let mut total_points = 0;
+        let mut total_vectors = 0;
+
+        // Collections with more points than the configured threshold
+        let mut large_collections = 0;
+
+        // Points per group of collections, derived from the collection name
+        let mut points_per_group = HashMap::<String, usize>::new();
+
         // Vectors excluded from index-only requests.
         let mut indexed_only_excluded = vec![];
 
@@ -191,6 +962,56 @@ impl MetricsProvider for CollectionsTelemetry {
 
         let mut vector_count_by_name = vec![];
 
+        // Shard keys per collection, only for collections with custom sharding
+        let mut shard_keys_per_collection = vec![];
+
+        // Share of points having each vector per collection
+        let mut vector_coverage_per_collection = vec![];
+
+        // Shards per collection, including inactive ones
+        let mut shards_per_collection = vec![];
+
+        // Indexed payload fields per collection
+        let mut payload_indexes_per_collection = vec![];
+
+        // Minimum active replicas relative to the configured replication factor, per collection
+        let mut replication_health_per_collection = vec![];
+
+        // Whether the HNSW index of each vector is stored on disk
+        let mut hnsw_on_disk_per_vector = vec![];
+
+        // Configured datatype of each vector
+        let mut datatype_per_vector = vec![];
+
+        // Configured quantization type per collection
+        let mut quantization_per_collection = vec![];
+
+        // Replicas eligible to serve reads per collection, over all its shards
+        let mut serving_replicas_per_collection = vec![];
+
+        // Failed optimizations per collection, as far as kept in the bounded optimizer log. Old
+        // entries are evicted, so this is not monotonic and can't be a counter.
+        let mut recent_optimizer_failures_per_collection = vec![];
+
+        // Segments under optimization per collection
+        let mut segments_optimizing_per_collection = vec![];
+
+        // Points indexed for all of their vectors per collection
+        let mut indexed_points_per_collection = vec![];
+
+        // Smallest, largest and average disk usage of local segments per collection
+        let mut segment_size_min_per_collection = vec![];
+        let mut segment_size_max_per_collection = vec![];
+        let mut segment_size_avg_per_collection = vec![];
+
+        // Local segments per collection, and the number targeted by the optimizer config
+        let mut segments_per_collection = vec![];
+        let mut target_segments_per_collection = vec![];
+
+        // Soft-deleted vectors per collection, and their ratio to all stored vectors
+        let mut deleted_vectors_per_collection = vec![];
+        let mut deleted_ratio_per_collection = vec![];
+
         for collection in self.collections.iter().flatten() {
             let collection = match collection {
                 CollectionTelemetryEnum::Full(collection_telemetry) => collection_telemetry,
@@ -200,6 +1021,39 @@ impl MetricsProvider for CollectionsTelemetry {
             };
 
             total_optimizations_running += collection.count_optimizers_running();
+            active_transfers += collection.transfers.iter().flatten().count();
+
+            let optimizer_failures = collection
+                .shards
+                .iter()
+                .flatten()
+                .filter_map(|shard| shard.local.as_ref())
+                .flat_map(|local| local.optimizations.log.iter().flatten())
+                .filter(|tracker| matches!(tracker.status, TrackerStatus::Error(_)))
+                .count();
+            recent_optimizer_failures_per_collection
+                .push(gauge(optimizer_failures as f64, &[("id", &collection.id)]));
+
+            // Segment IDs are unique within a shard only
+            let segments_optimizing: usize = collection
+                .shards
+                .iter()
+                .flatten()
+                .filter_map(|shard| shard.local.as_ref())
+                .map(|local| {
+                    local
+                        .optimizations
+                        .log
+                        .iter()
+                        .flatten()
+                        .filter(|tracker| tracker.status == TrackerStatus::Optimizing)
+                        .flat_map(|tracker| &tracker.segment_ids)
+                        .unique()
+                        .count()
+                })
+                .sum();
+            segments_optimizing_per_collection
+                .push(gauge(segments_optimizing as f64, &[("id", &collection.id)]));
 
             let min_max_active_replicas = collection
                 .shards
@@ -255,18 +1109,53 @@ impl MetricsProvider for CollectionsTelemetry {
             if let Some((min, max)) = min_max_active_replicas {
                 total_min_active_replicas = total_min_active_replicas.min(min);
                 total_max_active_replicas = total_max_active_replicas.max(max);
+
+                let replication_factor = collection.config.params.replication_factor.get();
+                let ratio = (min as f64 / f64::from(replication_factor)).clamp(0.0, 1.0);
+                replication_health_per_collection.push(gauge(ratio, &[("id", &collection.id)]));
             }
 
-            points_per_collection.push(gauge(
-                collection.count_points() as f64,
-                &[("id", &collection.id)],
-            ));
+            let serving_replicas = collection
+                .shards
+                .iter()
+                .flatten()
+                .flat_map(|shard| shard.replicate_states.values())
+                .filter(|state| state.is_active())
+                .count();
+            serving_replicas_per_collection
+                .push(gauge(serving_replicas as f64, &[("id", &collection.id)]));
+
+            let collection_points = collection.count_points();
+            total_points += collection_points;
+            total_vectors += collection.count_vectors();
+            if config
+                .large_collection_points_threshold
+                .is_some_and(|threshold| collection_points > threshold)
+            {
+                large_collections += 1;
+            }
+
+            points_per_collection.push(gauge(collection_points as f64, &[("id", &collection.id)]));
+
+            if let Some(group) =
+                group_regex.and_then(|regex| collection_group(regex, &collection.id))
+            {
+                *points_per_group.entry(group.to_string()).or_insert(0) += collection_points;
+            }
 
             for (vec_name, count) in collection.count_points_per_vector() {
                 vector_count_by_name.push(gauge(
                     count as f64,
                     &[("collection", &collection.id), ("vector", &vec_name)],
-                ))
+                ));
+
+                if collection_points > 0 {
+                    let coverage = (count as f64 / collection_points as f64).clamp(0.0, 1.0);
+                    vector_coverage_per_collection.push(gauge(
+                        coverage,
+                        &[("id", &collection.id), ("vector", &vec_name)],
+                    ));
+                }
             }
 
             let points_excluded_from_index_only = collection
@@ -284,11 +1173,141 @@ impl MetricsProvider for CollectionsTelemetry {
                     },
                 );
 
-            for (name, vector_size) in points_excluded_from_index_only {
-                indexed_only_excluded.push(gauge(
-                    vector_size as f64,
-                    &[("id", &collection.id), ("vector", name)],
-                ))
+            // Skip collections with too few excluded points to be actionable, if configured
+            let total_excluded: usize = points_excluded_from_index_only.values().sum();
+            let exceeds_threshold = config
+                .indexed_only_excluded_threshold
+                .is_none_or(|threshold| total_excluded > threshold);
+
+            if exceeds_threshold {
+                for (name, vector_size) in points_excluded_from_index_only {
+                    indexed_only_excluded.push(gauge(
+                        vector_size as f64,
+                        &[("id", &collection.id), ("vector", name)],
+                    ))
+                }
+            }
+
+            if let Some(shards) = &collection.shards {
+                shards_per_collection.push(gauge(shards.len() as f64, &[("id", &collection.id)]));
+            }
+
+            let shard_key_count = collection
+                .shards
+                .iter()
+                .flatten()
+                .filter_map(|shard| shard.key.as_ref())
+                .unique()
+                .count();
+            if shard_key_count > 0 {
+                shard_keys_per_collection
+                    .push(gauge(shard_key_count as f64, &[("id", &collection.id)]));
+            }
+
+            let quantization_type = match &collection.config.quantization_config {
+                None => "none",
+                Some(QuantizationConfig::Scalar(_)) => "scalar",
+                Some(QuantizationConfig::Product(_)) => "product",
+                Some(QuantizationConfig::Binary(_)) => "binary",
+            };
+            quantization_per_collection.push(gauge(
+                1.0,
+                &[("id", &collection.id), ("type", quantization_type)],
+            ));
+
+            for (vector_name, vector_params) in collection.config.params.vectors.params_iter() {
+                let on_disk = vector_params
+                    .hnsw_config
+                    .and_then(|hnsw_config| hnsw_config.on_disk)
+                    .or(collection.config.hnsw_config.on_disk)
+                    .unwrap_or(false);
+                hnsw_on_disk_per_vector.push(gauge(
+                    if on_disk { 1.0 } else { 0.0 },
+                    &[("id", &collection.id), ("vector", vector_name)],
+                ));
+
+                let datatype = match vector_params.datatype.unwrap_or_default() {
+                    Datatype::Float32 => "float32",
+                    Datatype::Uint8 => "uint8",
+                    Datatype::Float16 => "float16",
+                };
+                datatype_per_vector.push(gauge(
+                    1.0,
+                    &[
+                        ("id", &collection.id),
+                        ("vector", vector_name),
+                        ("datatype", datatype),
+                    ],
+                ));
+            }
+
+            // Segment telemetry is required to count indexed payload fields
+            if collection_segments(collection).next().is_some() {
+                let payload_indexes = collection_segments(collection)
+                    .flat_map(|segment| segment.info.index_schema.keys())
+                    .unique()
+                    .count();
+                payload_indexes_per_collection
+                    .push(gauge(payload_indexes as f64, &[("id", &collection.id)]));
+
+                // A point is only fully indexed once all of its vectors are
+                let indexed_points: usize = collection_segments(collection)
+                    .map(|segment| {
+                        segment
+                            .info
+                            .vector_data
+                            .values()
+                            .map(|vector_data| vector_data.num_indexed_vectors)
+                            .min()
+                            .unwrap_or(segment.info.num_points)
+                            .min(segment.info.num_points)
+                    })
+                    .sum();
+                indexed_points_per_collection
+                    .push(gauge(indexed_points as f64, &[("id", &collection.id)]));
+
+                let segment_sizes = collection_segments(collection)
+                    .map(|segment| segment.info.disk_usage_bytes)
+                    .collect_vec();
+                let labels = [("id", collection.id.as_str())];
+                let min = segment_sizes.iter().copied().min().unwrap_or_default();
+                let max = segment_sizes.iter().copied().max().unwrap_or_default();
+                let avg = segment_sizes.iter().sum::<usize>() as f64 / segment_sizes.len() as f64;
+                segment_size_min_per_collection.push(gauge(min as f64, &labels));
+                segment_size_max_per_collection.push(gauge(max as f64, &labels));
+                segment_size_avg_per_collection.push(gauge(avg, &labels));
+
+                // The target number of segments applies to each local shard
+                let segments = collection_segments(collection).count();
+                let local_shards = collection
+                    .shards
+                    .iter()
+                    .flatten()
+                    .filter_map(|shard| shard.local.as_ref())
+                    .filter(|local| local.segments.is_some())
+                    .count();
+                let target_segments =
+                    collection.config.optimizer_config.get_number_segments() * local_shards;
+                segments_per_collection.push(gauge(segments as f64, &[("id", &collection.id)]));
+                target_segments_per_collection
+                    .push(gauge(target_segments as f64, &[("id", &collection.id)]));
+
+                let (deleted_vectors, live_vectors) = collection_segments(collection)
+                    .flat_map(|segment| segment.info.vector_data.values())
+                    .fold((0, 0), |(deleted, live), vector_data| {
+                        (
+                            deleted + vector_data.num_deleted_vectors,
+                            live + vector_data.num_vectors,
+                        )
+                    });
+                let deleted_ratio = if deleted_vectors + live_vectors > 0 {
+                    deleted_vectors as f64 / (deleted_vectors + live_vectors) as f64
+                } else {
+                    0.0
+                };
+                deleted_vectors_per_collection
+                    .push(gauge(deleted_vectors as f64, &[("id", &collection.id)]));
+                deleted_ratio_per_collection.push(gauge(deleted_ratio, &[("id", &collection.id)]));
             }
 
             total_dead_replicas += collection
@@ -341,36 +1360,291 @@ impl MetricsProvider for CollectionsTelemetry {
             prefix,
         ));
 
-        metrics.push(metric_family(
-            "optimizer_running_processes",
-            "number of currently running optimization processes",
-            MetricType::GAUGE,
-            vec![gauge(total_optimizations_running as f64, &[])],
-            prefix,
-        ));
+        if !replication_health_per_collection.is_empty() {
+            metrics.push(metric_family(
+                "collection_replication_health_ratio",
+                "minimum number of active replicas relative to the configured replication factor",
+                MetricType::GAUGE,
+                replication_health_per_collection,
+                prefix,
+            ));
+        }
 
-        if !points_per_collection.is_empty() {
+        if !datatype_per_vector.is_empty() {
             metrics.push(metric_family(
-                "collection_points",
-                "approximate amount of points per collection",
+                "collection_vector_datatype",
+                "datatype configured for a vector",
                 MetricType::GAUGE,
-                points_per_collection,
+                datatype_per_vector,
                 prefix,
             ));
         }
 
-        metrics.push(metric_family(
-            "dead_replicas",
-            "total amount of shard replicas in non-active state",
-            MetricType::GAUGE,
-            vec![gauge(total_dead_replicas as f64, &[])],
-            prefix,
-        ));
+        if !quantization_per_collection.is_empty() {
+            metrics.push(metric_family(
+                "collection_quantization",
+                "quantization type configured for a collection",
+                MetricType::GAUGE,
+                quantization_per_collection,
+                prefix,
+            ));
+        }
+
+        if !hnsw_on_disk_per_vector.is_empty() {
+            metrics.push(metric_family(
+                "collection_hnsw_on_disk",
+                "whether the HNSW index of a vector is stored on disk",
+                MetricType::GAUGE,
+                hnsw_on_disk_per_vector,
+                prefix,
+            ));
+        }
+
+        metrics.push(metric_family(
+            "optimizer_running_processes",
+            "number of currently running optimization processes",
+            MetricType::GAUGE,
+            vec![gauge(total_optimizations_running as f64, &[])],
+            prefix,
+        ));
+
+        metrics.push(metric_family(
+            "shard_transfers_active",
+            "number of shard transfers in progress over all collections",
+            MetricType::GAUGE,
+            vec![gauge(active_transfers as f64, &[])],
+            prefix,
+        ));
+
+        if !points_per_collection.is_empty() {
+            metrics.push(metric_family(
+                "collection_points",
+                "approximate amount of points per collection",
+                MetricType::GAUGE,
+                points_per_collection,
+                prefix,
+            ));
+        }
+
+        if config.aggregate_collection_metrics {
+            metrics.push(metric_family(
+                "points_total",
+                "approximate amount of points over all collections",
+                MetricType::GAUGE,
+                vec![gauge(total_points as f64, &[])],
+                prefix,
+            ));
+            metrics.push(metric_family(
+                "vectors_total",
+                "approximate amount of vectors over all collections",
+                MetricType::GAUGE,
+                vec![gauge(total_vectors as f64, &[])],
+                prefix,
+            ));
+        }
+
+        if config.large_collection_points_threshold.is_some() {
+            metrics.push(metric_family(
+                "large_collections_total",
+                "number of collections with more points than the configured threshold",
+                MetricType::GAUGE,
+                vec![gauge(f64::from(large_collections), &[])],
+                prefix,
+            ));
+        }
+
+        if !points_per_group.is_empty() {
+            metrics.push(metric_family(
+                "collection_group_points",
+                "approximate amount of points per group of collections",
+                MetricType::GAUGE,
+                points_per_group
+                    .iter()
+                    .map(|(group, points)| gauge(*points as f64, &[("group", group)]))
+                    .collect(),
+                prefix,
+            ));
+        }
+
+        if !shard_keys_per_collection.is_empty() {
+            metrics.push(metric_family(
+                "collection_shard_key_ranges",
+                "number of shard keys of collections using custom sharding",
+                MetricType::GAUGE,
+                shard_keys_per_collection,
+                prefix,
+            ));
+        }
+
+        if !vector_coverage_per_collection.is_empty() {
+            metrics.push(metric_family(
+                "collection_vector_coverage_ratio",
+                "ratio of points having a vector to all points per collection and vector name",
+                MetricType::GAUGE,
+                vector_coverage_per_collection,
+                prefix,
+            ));
+        }
+
+        if !shards_per_collection.is_empty() {
+            metrics.push(metric_family(
+                "collection_shards_total",
+                "number of shards per collection",
+                MetricType::GAUGE,
+                shards_per_collection,
+                prefix,
+            ));
+        }
+
+        if !payload_indexes_per_collection.is_empty() {
+            metrics.push(metric_family(
+                "collection_payload_indexes",
+                "number of indexed payload fields per collection",
+                MetricType::GAUGE,
+                payload_indexes_per_collection,
+                prefix,
+            ));
+        }
+
+        if !serving_replicas_per_collection.is_empty() {
+            metrics.push(metric_family(
+                "collection_serving_replicas",
+                "number of active replicas eligible to serve reads per collection",
+                MetricType::GAUGE,
+                serving_replicas_per_collection,
+                prefix,
+            ));
+        }
+
+        if !recent_optimizer_failures_per_collection.is_empty() {
+            metrics.push(metric_family(
+                "collection_recent_optimizer_failures",
+                "number of failed optimizations per collection in the recent optimizer log",
+                MetricType::GAUGE,
+                recent_optimizer_failures_per_collection,
+                prefix,
+            ));
+        }
+
+        if !segments_optimizing_per_collection.is_empty() {
+            metrics.push(metric_family(
+                "collection_segments_optimizing",
+                "number of segments currently being optimized per collection",
+                MetricType::GAUGE,
+                segments_optimizing_per_collection,
+                prefix,
+            ));
+        }
+
+        if !indexed_points_per_collection.is_empty() {
+            metrics.push(metric_family(
+                "collection_indexed_points",
+                "number of points indexed for all of their vectors per collection",
+                MetricType::GAUGE,
+                indexed_points_per_collection,
+                prefix,
+            ));
+        }
+
+        if !segment_size_max_per_collection.is_empty() {
+            metrics.push(metric_family(
+                "collection_segment_size_min_bytes",
+                "disk usage of the smallest local segment per collection",
+                MetricType::GAUGE,
+                segment_size_min_per_collection,
+                prefix,
+            ));
+            metrics.push(metric_family(
+                "collection_segment_size_max_bytes",
+                "disk usage of the largest local segment per collection",
+                MetricType::GAUGE,
+                segment_size_max_per_collection,
+                prefix,
+            ));
+            metrics.push(metric_family(
+                "collection_segment_size_avg_bytes",
+                "average disk usage of local segments per collection",
+                MetricType::GAUGE,
+                segment_size_avg_per_collection,
+                prefix,
+            ));
+        }
+
+        if !segments_per_collection.is_empty() {
+            metrics.push(metric_family(
+                "collection_segments",
+                "number of local segments per collection",
+                MetricType::GAUGE,
+                segments_per_collection,
+                prefix,
+            ));
+            metrics.push(metric_family(
+                "collection_target_segments",
+                "number of local segments per collection targeted by the optimizer config",
+                MetricType::GAUGE,
+                target_segments_per_collection,
+                prefix,
+            ));
+        }
+
+        if !deleted_vectors_per_collection.is_empty() {
+            metrics.push(metric_family(
+                "collection_deleted_vectors",
+                "number of soft-deleted vectors per collection, pending compaction",
+                MetricType::GAUGE,
+                deleted_vectors_per_collection,
+                prefix,
+            ));
+            metrics.push(metric_family(
+                "collection_deleted_ratio",
+                "ratio of soft-deleted vectors to all stored vectors per collection",
+                MetricType::GAUGE,
+                deleted_ratio_per_collection,
+                prefix,
+            ));
+        }
+
+        metrics.push(metric_family(
+            "dead_replicas",
+            "total amount of shard replicas in non-active state",
+            MetricType::GAUGE,
+            vec![gauge(total_dead_replicas as f64, &[])],
+            prefix,
+        ));
     }
 }
 
+/// Iterate over the telemetry of all local segments of a collection.
+fn collection_segments(
+    collection: &CollectionTelemetry,
+) -> impl Iterator<Item = &SegmentTelemetry> {
+    collection
+        .shards
+        .iter()
+        .flatten()
+        .filter_map(|shard| shard.local.as_ref())
+        .filter_map(|local| local.segments.as_ref())
+        .flatten()
+}
+
+/// Derive the group of a collection from its name.
+///
+/// Uses the first capture group of the regex if it has one, the whole match otherwise.
+fn collection_group<'a>(regex: &Regex, collection_name: &'a str) -> Option<&'a str> {
+    let captures = regex.captures(collection_name)?;
+    captures
+        .get(1)
+        .or_else(|| captures.get(0))
+        .map(|group| group.as_str())
+}
+
 impl MetricsProvider for ClusterTelemetry {
-    fn add_metrics(&self, metrics: &mut Vec<MetricFamily>, prefix: Option<&str>) {
+    fn add_metrics(
+        &self,
+        metrics: &mut Vec<MetricFamily>,
+        prefix: Option<&str>,
+        config: &MetricsConfig,
+    ) {
         let ClusterTelemetry {
             enabled,
             status,
@@ -389,13 +1663,18 @@ impl MetricsProvider for ClusterTelemetry {
         ));
 
         if let Some(status) = status {
-            status.add_metrics(metrics, prefix);
+            status.add_metrics(metrics, prefix, config);
         }
     }
 }
 
 impl MetricsProvider for ClusterStatusTelemetry {
-    fn add_metrics(&self, metrics: &mut Vec<MetricFamily>, prefix: Option<&str>) {
+    fn add_metrics(
+        &self,
+        metrics: &mut Vec<MetricFamily>,
+        prefix: Option<&str>,
+        _config: &MetricsConfig,
+    ) {
         metrics.push(metric_family(
             "cluster_peers_total",
             "total number of cluster peers",
@@ -434,27 +1713,54 @@ impl MetricsProvider for ClusterStatusTelemetry {
                 prefix,
             ));
         }
+
+        if let Some(ref leader) = self.leader.map(|p| p.to_string()) {
+            metrics.push(metric_family(
+                "cluster_leader",
+                "current leader of the cluster, as known by this peer",
+                MetricType::GAUGE,
+                vec![gauge(1.0, &[("peer_id", leader)])],
+                prefix,
+            ));
+        }
     }
 }
 
 impl MetricsProvider for RequestsTelemetry {
-    fn add_metrics(&self, metrics: &mut Vec<MetricFamily>, prefix: Option<&str>) {
-        self.rest.add_metrics(metrics, prefix);
-        self.grpc.add_metrics(metrics, prefix);
+    fn add_metrics(
+        &self,
+        metrics: &mut Vec<MetricFamily>,
+        prefix: Option<&str>,
+        config: &MetricsConfig,
+    ) {
+        self.rest.add_metrics(metrics, prefix, config);
+        self.grpc.add_metrics(metrics, prefix, config);
     }
 }
 
 impl MetricsProvider for WebApiTelemetry {
-    fn add_metrics(&self, metrics: &mut Vec<MetricFamily>, prefix: Option<&str>) {
-        let mut builder = OperationDurationMetricsBuilder::default();
+    fn add_metrics(
+        &self,
+        metrics: &mut Vec<MetricFamily>,
+        prefix: Option<&str>,
+        config: &MetricsConfig,
+    ) {
+        let mut builder = OperationDurationMetricsBuilder::new(config);
         for (endpoint, responses) in &self.responses {
             let Some((method, endpoint)) = endpoint.split_once(' ') else {
                 continue;
             };
             // Endpoint must be whitelisted
-            if REST_ENDPOINT_WHITELIST.binary_search(&endpoint).is_err() {
+            let Ok(index) =
+                REST_ENDPOINT_WHITELIST.binary_search_by_key(&endpoint, |&(endpoint, _)| endpoint)
+            else {
                 continue;
-            }
+            };
+            let endpoint = if config.compact_endpoint_labels {
+                REST_ENDPOINT_WHITELIST[index].1
+            } else {
+                endpoint
+            };
             for (status, stats) in responses {
                 builder.add(
                     stats,
@@ -467,13 +1773,32 @@ impl MetricsProvider for WebApiTelemetry {
                 );
             }
         }
-        builder.build(prefix, "rest", metrics);
+        let rest_prefix = config.rest_prefix.as_deref().unwrap_or("rest");
+        builder.build(prefix, rest_prefix, metrics);
+
+        if config.compact_endpoint_labels {
+            metrics.push(metric_family(
+                &join_prefix(None, Some(rest_prefix), "endpoint_info"),
+                "mapping of compact endpoint labels to REST endpoints",
+                MetricType::GAUGE,
+                REST_ENDPOINT_WHITELIST
+                    .iter()
+                    .map(|&(endpoint, code)| gauge(1.0, &[("endpoint", endpoint), ("code", code)]))
+                    .collect(),
+                prefix,
+            ));
+        }
     }
 }
 
 impl MetricsProvider for GrpcTelemetry {
-    fn add_metrics(&self, metrics: &mut Vec<MetricFamily>, prefix: Option<&str>) {
-        let mut builder = OperationDurationMetricsBuilder::default();
+    fn add_metrics(
+        &self,
+        metrics: &mut Vec<MetricFamily>,
+        prefix: Option<&str>,
+        config: &MetricsConfig,
+    ) {
+        let mut builder = OperationDurationMetricsBuilder::new(config);
         for (endpoint, stats) in &self.responses {
             // Endpoint must be whitelisted
             if GRPC_ENDPOINT_WHITELIST
@@ -484,12 +1809,58 @@ impl MetricsProvider for GrpcTelemetry {
             }
             builder.add(stats, &[("endpoint", endpoint.as_str())], true);
         }
-        builder.build(prefix, "grpc", metrics);
+
+        // Expose unused endpoints with zero responses, so their series exist from startup. Not
+        // done for REST, where the method and status labels are only known from responses.
+        if config.preregister_endpoints {
+            for &endpoint in GRPC_ENDPOINT_WHITELIST {
+                if !self.responses.contains_key(endpoint) {
+                    builder.add(
+                        &OperationDurationStatistics::default(),
+                        &[("endpoint", endpoint)],
+                        false,
+                    );
+                }
+            }
+        }
+        let grpc_prefix = config.grpc_prefix.as_deref().unwrap_or("grpc");
+        builder.build(prefix, grpc_prefix, metrics);
+
+        let mut responses_by_code = vec![];
+        for (endpoint, codes) in &self.responses_by_code {
+            // Endpoint must be whitelisted
+            if GRPC_ENDPOINT_WHITELIST
+                .binary_search(&endpoint.as_str())
+                .is_err()
+            {
+                continue;
+            }
+            for (code, count) in codes {
+                responses_by_code.push(counter(
+                    *count as f64,
+                    &[("endpoint", endpoint.as_str()), ("code", code.as_str())],
+                ));
+            }
+        }
+        if !responses_by_code.is_empty() {
+            metrics.push(metric_family(
+                &join_prefix(None, Some(grpc_prefix), "responses_by_code_total"),
+                "total number of responses grouped by gRPC status code, unknown if sent in trailers",
+                MetricType::COUNTER,
+                responses_by_code,
+                prefix,
+            ));
+        }
     }
 }
 
 impl MetricsProvider for MemoryTelemetry {
-    fn add_metrics(&self, metrics: &mut Vec<MetricFamily>, prefix: Option<&str>) {
+    fn add_metrics(
+        &self,
+        metrics: &mut Vec<MetricFamily>,
+        prefix: Option<&str>,
+        config: &MetricsConfig,
+    ) {
         metrics.push(metric_family(
             "memory_active_bytes",
             "Total number of bytes in active pages allocated by the application",
@@ -525,21 +1896,56 @@ impl MetricsProvider for MemoryTelemetry {
             vec![gauge(self.retained_bytes as f64, &[])],
             prefix,
         ));
+
+        if let Some(quota) = config.memory_quota_bytes {
+            metrics.push(metric_family(
+                "node_memory_quota_bytes",
+                "Memory quota configured for this node",
+                MetricType::GAUGE,
+                vec![gauge(quota as f64, &[])],
+                prefix,
+            ));
+
+            let utilization = if quota == 0 {
+                0.0
+            } else {
+                self.resident_bytes as f64 / quota as f64
+            };
+            metrics.push(metric_family(
+                "node_memory_quota_utilization",
+                "Ratio of resident bytes to the memory quota of this node",
+                MetricType::GAUGE,
+                vec![gauge(utilization, &[])],
+                prefix,
+            ));
+        }
     }
 }
 
 impl HardwareTelemetry {
     // Helper function to create counter metrics of a single Hw type, like cpu.
-    fn make_metric_counters<F: Fn(&HardwareUsage) -> usize>(&self, f: F) -> Vec<Metric> {
+    // Zero values are skipped if `skip_zero` is set.
+    fn make_metric_counters<F: Fn(&HardwareUsage) -> usize>(
+        &self,
+        f: F,
+        skip_zero: bool,
+    ) -> Vec<Metric> {
         self.collection_data
             .iter()
-            .map(|(collection_id, hw_usage)| counter(f(hw_usage) as f64, &[("id", collection_id)]))
+            .map(|(collection_id, hw_usage)| (collection_id, f(hw_usage)))
+            .filter(|(_, value)| !skip_zero || *value > 0)
+            .map(|(collection_id, value)| counter(value as f64, &[("id", collection_id)]))
             .collect()
     }
 }
 
 impl MetricsProvider for HardwareTelemetry {
-    fn add_metrics(&self, metrics: &mut Vec<MetricFamily>, prefix: Option<&str>) {
+    fn add_metrics(
+        &self,
+        metrics: &mut Vec<MetricFamily>,
+        prefix: Option<&str>,
+        config: &MetricsConfig,
+    ) {
         // MetricType::COUNTER requires non-empty collection data.
         if self.collection_data.is_empty() {
             return;
@@ -557,61 +1963,61 @@ impl MetricsProvider for HardwareTelemetry {
             vector_io_write: _,
         } = HardwareUsage::default();
 
-        metrics.push(metric_family(
+        let skip_zero = config.skip_zero_hardware_counters;
+        let mut push_counters = |name: &str, help: &str, counters: Vec<Metric>| {
+            // Skipping zero values may leave a counter without any collection data
+            if !counters.is_empty() {
+                metrics.push(metric_family(
+                    name,
+                    help,
+                    MetricType::COUNTER,
+                    counters,
+                    prefix,
+                ));
+            }
+        };
+
+        push_counters(
             "collection_hardware_metric_cpu",
             "CPU measurements of a collection",
-            MetricType::COUNTER,
-            self.make_metric_counters(|hw| hw.cpu),
-            prefix,
-        ));
+            self.make_metric_counters(|hw| hw.cpu, skip_zero),
+        );
 
-        metrics.push(metric_family(
+        push_counters(
             "collection_hardware_metric_payload_io_read",
             "Total IO payload read metrics of a collection",
-            MetricType::COUNTER,
-            self.make_metric_counters(|hw| hw.payload_io_read),
-            prefix,
-        ));
+            self.make_metric_counters(|hw| hw.payload_io_read, skip_zero),
+        );
 
-        metrics.push(metric_family(
+        push_counters(
             "collection_hardware_metric_payload_index_io_read",
             "Total IO payload index read metrics of a collection",
-            MetricType::COUNTER,
-            self.make_metric_counters(|hw| hw.payload_index_io_read),
-            prefix,
-        ));
+            self.make_metric_counters(|hw| hw.payload_index_io_read, skip_zero),
+        );
 
-        metrics.push(metric_family(
+        push_counters(
             "collection_hardware_metric_payload_index_io_write",
             "Total IO payload index write metrics of a collection",
-            MetricType::COUNTER,
-            self.make_metric_counters(|hw| hw.payload_index_io_write),
-            prefix,
-        ));
+            self.make_metric_counters(|hw| hw.payload_index_io_write, skip_zero),
+        );
 
-        metrics.push(metric_family(
+        push_counters(
             "collection_hardware_metric_payload_io_write",
             "Total IO payload write metrics of a collection",
-            MetricType::COUNTER,
-            self.make_metric_counters(|hw| hw.payload_io_write),
-            prefix,
-        ));
+            self.make_metric_counters(|hw| hw.payload_io_write, skip_zero),
+        );
 
-        metrics.push(metric_family(
+        push_counters(
             "collection_hardware_metric_vector_io_read",
             "Total IO vector read metrics of a collection",
-            MetricType::COUNTER,
-            self.make_metric_counters(|hw| hw.vector_io_read),
-            prefix,
-        ));
+            self.make_metric_counters(|hw| hw.vector_io_read, skip_zero),
+        );
 
-        metrics.push(metric_family(
+        push_counters(
             "collection_hardware_metric_vector_io_write",
             "Total IO vector write metrics of a collection",
-            MetricType::COUNTER,
-            self.make_metric_counters(|hw| hw.vector_io_write),
-            prefix,
-        ));
+            self.make_metric_counters(|hw| hw.vector_io_write, skip_zero),
+        );
     }
 }
 
@@ -619,15 +2025,29 @@ impl MetricsProvider for HardwareTelemetry {
 /// [`OperationDurationStatistics`].
 #[derive(Default)]
 struct OperationDurationMetricsBuilder {
+    unit: MetricsDurationUnit,
+    skip_histograms: bool,
+    summaries: bool,
     total: Vec<Metric>,
     fail_total: Vec<Metric>,
-    avg_secs: Vec<Metric>,
-    min_secs: Vec<Metric>,
-    max_secs: Vec<Metric>,
-    duration_histogram_secs: Vec<Metric>,
+    avg: Vec<Metric>,
+    min: Vec<Metric>,
+    max: Vec<Metric>,
+    duration_histogram: Vec<Metric>,
+    duration_summary: Vec<Metric>,
 }
 
 impl OperationDurationMetricsBuilder {
+    /// Create a builder reporting durations as configured.
+    pub fn new(config: &MetricsConfig) -> Self {
+        Self {
+            unit: config.duration_unit,
+            skip_histograms: config.skip_duration_histograms,
+            summaries: config.duration_summaries,
+            ..Default::default()
+        }
+    }
+
     /// Add metrics for the provided statistics.
     /// If `add_timings` is `false`, only the total and fail_total counters will be added.
     pub fn add(
@@ -644,86 +2064,127 @@ impl OperationDurationMetricsBuilder {
             return;
         }
 
-        self.avg_secs.push(gauge(
-            f64::from(stat.avg_duration_micros.unwrap_or(0.0)) / 1_000_000.0,
-            labels,
-        ));
-        self.min_secs.push(gauge(
-            f64::from(stat.min_duration_micros.unwrap_or(0.0)) / 1_000_000.0,
+        // Histogram sum and bounds must use the same unit as the gauges
+        let micros_per_unit = self.unit.micros_per_unit();
+
+        self.avg.push(gauge(
+            f64::from(stat.avg_duration_micros.unwrap_or(0.0)) / micros_per_unit,
             labels,
         ));
-        self.max_secs.push(gauge(
-            f64::from(stat.max_duration_micros.unwrap_or(0.0)) / 1_000_000.0,
+        self.min.push(gauge(
+            f64::from(stat.min_duration_micros.unwrap_or(0.0)) / micros_per_unit,
             labels,
         ));
-        self.duration_histogram_secs.push(histogram(
-            stat.count as u64,
-            stat.total_duration_micros.unwrap_or(0) as f64 / 1_000_000.0,
-            &stat
-                .duration_micros_histogram
-                .iter()
-                .map(|&(b, c)| (f64::from(b) / 1_000_000.0, c as u64))
-                .collect::<Vec<_>>(),
+        self.max.push(gauge(
+            f64::from(stat.max_duration_micros.unwrap_or(0.0)) / micros_per_unit,
             labels,
         ));
+
+        // Summaries are small, so they are reported even if histograms are skipped
+        if self.skip_histograms && !self.summaries {
+            return;
+        }
+        let sample_count = stat.count as u64;
+        let sample_sum = stat.total_duration_micros.unwrap_or(0) as f64 / micros_per_unit;
+        let buckets = stat
+            .duration_micros_histogram
+            .iter()
+            .map(|&(b, c)| (f64::from(b) / micros_per_unit, c as u64))
+            .collect::<Vec<_>>();
+        if self.summaries {
+            let max = f64::from(stat.max_duration_micros.unwrap_or(0.0)) / micros_per_unit;
+            self.duration_summary
+                .push(summary(sample_count, sample_sum, &buckets, max, labels));
+        } else {
+            self.duration_histogram
+                .push(histogram(sample_count, sample_sum, &buckets, labels));
+        }
     }
 
     /// Build metrics and add them to the provided vector.
     pub fn build(self, global_prefix: Option<&str>, prefix: &str, metrics: &mut Vec<MetricFamily>) {
-        let prefix = format!("{}{prefix}_", global_prefix.unwrap_or(""));
-
+        let unit = self.unit.name();
         if !self.total.is_empty() {
             metrics.push(metric_family(
-                "responses_total",
+                &join_prefix(global_prefix, Some(prefix), "responses_total"),
                 "total number of responses",
                 MetricType::COUNTER,
                 self.total,
-                Some(&prefix),
+                None,
             ));
         }
         if !self.fail_total.is_empty() {
             metrics.push(metric_family(
-                "responses_fail_total",
+                &join_prefix(global_prefix, Some(prefix), "responses_fail_total"),
                 "total number of failed responses",
                 MetricType::COUNTER,
                 self.fail_total,
-                Some(&prefix),
+                None,
             ));
         }
-        if !self.avg_secs.is_empty() {
+        if !self.avg.is_empty() {
             metrics.push(metric_family(
-                "responses_avg_duration_seconds",
+                &join_prefix(
+                    global_prefix,
+                    Some(prefix),
+                    &format!("responses_avg_duration_{unit}"),
+                ),
                 "average response duration",
                 MetricType::GAUGE,
-                self.avg_secs,
-                Some(&prefix),
+                self.avg,
+                None,
             ));
         }
-        if !self.min_secs.is_empty() {
+        if !self.min.is_empty() {
             metrics.push(metric_family(
-                "responses_min_duration_seconds",
+                &join_prefix(
+                    global_prefix,
+                    Some(prefix),
+                    &format!("responses_min_duration_{unit}"),
+                ),
                 "minimum response duration",
                 MetricType::GAUGE,
-                self.min_secs,
-                Some(&prefix),
+                self.min,
+                None,
             ));
         }
-        if !self.max_secs.is_empty() {
+        if !self.max.is_empty() {
             metrics.push(metric_family(
-                "responses_max_duration_seconds",
+                &join_prefix(
+                    global_prefix,
+                    Some(prefix),
+                    &format!("responses_max_duration_{unit}"),
+                ),
                 "maximum response duration",
                 MetricType::GAUGE,
-                self.max_secs,
-                Some(&prefix),
+                self.max,
+                None,
             ));
         }
-        if !self.duration_histogram_secs.is_empty() {
+        if !self.duration_histogram.is_empty() {
             metrics.push(metric_family(
-                "responses_duration_seconds",
+                &join_prefix(
+                    global_prefix,
+                    Some(prefix),
+                    &format!("responses_duration_{unit}"),
+                ),
                 "response duration histogram",
                 MetricType::HISTOGRAM,
-                self.duration_histogram_secs,
-                Some(&prefix),
+                self.duration_histogram,
+                None,
+            ));
+        }
+        if !self.duration_summary.is_empty() {
+            metrics.push(metric_family(
+                &join_prefix(
+                    global_prefix,
+                    Some(prefix),
+                    &format!("responses_duration_summary_{unit}"),
+                ),
+                "response duration quantiles, estimated from the duration histogram",
+                MetricType::SUMMARY,
+                self.duration_summary,
+                None,
             ));
         }
     }
@@ -738,17 +2199,58 @@ fn metric_family(
 ) -> MetricFamily {
     let mut metric_family = MetricFamily::default();
 
-    let name_with_prefix = prefix
-        .map(|prefix| format!("{prefix}{name}"))
-        .unwrap_or_else(|| name.to_string());
-
-    metric_family.set_name(name_with_prefix);
+    metric_family.set_name(join_prefix(prefix, None, name));
     metric_family.set_help(help.into());
     metric_family.set_field_type(r#type);
     metric_family.set_metric(metrics);
     metric_family
 }
 
+/// Writer only counting the bytes written to it.
+#[derive(Default)]
+struct CountingWriter {
+    bytes: usize,
+}
+
+impl io::Write for CountingWriter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.bytes += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Attach the given labels to all metrics of the given families.
+fn add_const_labels(metrics: &mut [MetricFamily], labels: &[(String, String)]) {
+    if labels.is_empty() {
+        return;
+    }
+
+    for family in metrics {
+        for metric in family.mut_metric() {
+            let mut metric_labels = metric.take_label();
+            metric_labels.extend(labels.iter().map(|(name, value)| label_pair(name, value)));
+            metric.set_label(metric_labels);
+        }
+    }
+}
+
+/// Join the global prefix, an optional sub-prefix and a metric name.
+///
+/// Prefixes are separated by a single underscore, whether or not they already end with one.
+fn join_prefix(global_prefix: Option<&str>, sub_prefix: Option<&str>, name: &str) -> String {
+    [global_prefix, sub_prefix]
+        .into_iter()
+        .flatten()
+        .map(|prefix| prefix.trim_end_matches('_'))
+        .filter(|prefix| !prefix.is_empty())
+        .chain([name])
+        .join("_")
+}
+
 fn counter(value: f64, labels: &[(&str, &str)]) -> Metric {
     let mut metric = Metric::default();
     metric.set_label(labels.iter().map(|(n, v)| label_pair(n, v)).collect());
@@ -760,6 +2262,39 @@ fn counter(value: f64, labels: &[(&str, &str)]) -> Metric {
     metric
 }
 
+/// Graphite path of a metric family, separating the global prefix by a dot instead of `_`.
+fn graphite_path(prefix: Option<&str>, name: &str) -> String {
+    let prefix = prefix.unwrap_or_default().trim_end_matches('_');
+    match name
+        .strip_prefix(prefix)
+        .and_then(|name| name.strip_prefix('_'))
+    {
+        Some(name) if !prefix.is_empty() => format!("{prefix}.{name}"),
+        _ => name.to_string(),
+    }
+}
+
+/// Format a label as Graphite tag, including its `;` separator.
+///
+/// Graphite doesn't allow `;` and whitespace in tag values, nor values that are empty or start
+/// with `~`. Such characters are replaced, labels with empty values are skipped.
+fn graphite_tag(name: &str, value: &str) -> Option<String> {
+    let value = value
+        .trim_start_matches('~')
+        .replace(|c: char| c == ';' || c.is_whitespace(), "_");
+    (!value.is_empty()).then(|| format!(";{name}={value}"))
+}
+
+/// Round a value to the given number of significant digits.
+fn round_significant(value: f64, digits: u32) -> f64 {
+    if value == 0.0 || !value.is_finite() {
+        return value;
+    }
+    let magnitude = value.abs().log10().floor() as i32;
+    let factor = 10f64.powi(digits as i32 - 1 - magnitude);
+    (value * factor).round() / factor
+}
+
 fn gauge(value: f64, labels: &[(&str, &str)]) -> Metric {
     let mut metric = Metric::default();
     metric.set_label(labels.iter().map(|(n, v)| label_pair(n, v)).collect());
@@ -799,6 +2334,67 @@ fn histogram(
     metric
 }
 
+/// Quantiles reported in response duration summaries.
+const SUMMARY_QUANTILES: [f64; 3] = [0.5, 0.9, 0.99];
+
+/// Summary with quantiles estimated from the given cumulative histogram buckets.
+///
+/// `max` bounds the implicit `+Inf` bucket.
+fn summary(
+    sample_count: u64,
+    sample_sum: f64,
+    buckets: &[(f64, u64)],
+    max: f64,
+    labels: &[(&str, &str)],
+) -> Metric {
+    let mut metric = Metric::default();
+    metric.set_label(labels.iter().map(|(n, v)| label_pair(n, v)).collect());
+    metric.set_summary({
+        let mut summary = prometheus::proto::Summary::default();
+        summary.set_sample_count(sample_count);
+        summary.set_sample_sum(sample_sum);
+        summary.set_quantile(
+            SUMMARY_QUANTILES
+                .iter()
+                .map(|&q| {
+                    let mut quantile = prometheus::proto::Quantile::default();
+                    quantile.set_quantile(q);
+                    quantile.set_value(histogram_quantile(q, sample_count, buckets, max));
+                    quantile
+                })
+                .collect(),
+        );
+        summary
+    });
+    metric
+}
+
+/// Estimate a quantile from cumulative histogram buckets, interpolating linearly within the
+/// bucket containing it, like Prometheus' `histogram_quantile`.
+///
+/// Samples above the last bucket are assumed to be bounded by `max`. Returns NaN if there are no
+/// samples.
+fn histogram_quantile(q: f64, sample_count: u64, buckets: &[(f64, u64)], max: f64) -> f64 {
+    if sample_count == 0 {
+        return f64::NAN;
+    }
+    let rank = q * sample_count as f64;
+    let inf_bound = buckets.last().map_or(max, |&(bound, _)| bound.max(max));
+
+    let (mut lower, mut lower_count) = (0.0, 0);
+    for (upper, cumulative_count) in buckets.iter().copied().chain([(inf_bound, sample_count)]) {
+        if cumulative_count as f64 >= rank {
+            let bucket_count = cumulative_count.saturating_sub(lower_count);
+            if bucket_count == 0 {
+                return upper;
+            }
+            return lower + (upper - lower) * (rank - lower_count as f64) / bucket_count as f64;
+        }
+        (lower, lower_count) = (upper, cumulative_count);
+    }
+    inf_bound
+}
+
 fn label_pair(name: &str, value: &str) -> LabelPair {
     let mut label = LabelPair::default();
     label.set_name(name.into());
@@ -806,8 +2402,125 @@ fn label_pair(name: &str, value: &str) -> LabelPair {
     label
 }
 
+/// Disk space of the volume containing the storage.
+struct StorageDiskMetrics {
+    free_bytes: u64,
+    total_bytes: u64,
+}
+
+impl StorageDiskMetrics {
+    fn collect(storage_path: &Path) -> io::Result<Self> {
+        Ok(Self {
+            free_bytes: fs4::available_space(storage_path)?,
+            total_bytes: fs4::total_space(storage_path)?,
+        })
+    }
+}
+
+impl MetricsProvider for StorageDiskMetrics {
+    fn add_metrics(
+        &self,
+        metrics: &mut Vec<MetricFamily>,
+        prefix: Option<&str>,
+        _config: &MetricsConfig,
+    ) {
+        metrics.push(metric_family(
+            "storage_disk_free_bytes",
+            "disk space available to qdrant on the storage volume",
+            MetricType::GAUGE,
+            vec![gauge(self.free_bytes as f64, &[])],
+            prefix,
+        ));
+        metrics.push(metric_family(
+            "storage_disk_total_bytes",
+            "total disk space of the storage volume",
+            MetricType::GAUGE,
+            vec![gauge(self.total_bytes as f64, &[])],
+            prefix,
+        ));
+    }
+}
+
+/// Metrics of a provider along with the procfs and cgroup metrics of the same scrape.
+#[cfg(target_os = "linux")]
+struct WithProcFs<'a, T> {
+    provider: &'a T,
+    procfs: Option<ProcFsMetrics>,
+    cgroup: Option<CgroupMetrics>,
+}
+
+#[cfg(target_os = "linux")]
+impl<T: MetricsProvider> MetricsProvider for WithProcFs<'_, T> {
+    fn add_metrics(
+        &self,
+        metrics: &mut Vec<MetricFamily>,
+        prefix: Option<&str>,
+        config: &MetricsConfig,
+    ) {
+        self.provider.add_metrics(metrics, prefix, config);
+        if let Some(procfs) = &self.procfs {
+            add_metrics_catch_unwind(procfs, metrics, prefix, config);
+        }
+        if let Some(cgroup) = &self.cgroup {
+            add_metrics_catch_unwind(cgroup, metrics, prefix, config);
+        }
+    }
+}
+
+/// Caches the expensive procfs thread and mmap counts, only counting them again every `interval`
+/// scrapes. Other procfs metrics are collected on every scrape.
+#[cfg(target_os = "linux")]
+#[derive(Debug)]
+struct ProcFsCache {
+    collect: fn() -> Result<ProcFsMetrics, procfs::ProcError>,
+    interval: usize,
+    scrapes: usize,
+    cached: Option<ProcFsMetrics>,
+}
+
+#[cfg(target_os = "linux")]
+impl ProcFsCache {
+    fn new(interval: usize) -> Self {
+        Self {
+            collect: ProcFsMetrics::collect,
+            interval: interval.max(1),
+            scrapes: 0,
+            cached: None,
+        }
+    }
+
+    /// Get the procfs metrics for the current scrape, counting threads and mmaps if due.
+    fn get(&mut self) -> Option<ProcFsMetrics> {
+        let result = match &self.cached {
+            Some(cached) if self.scrapes % self.interval != 0 => cached.refresh(),
+            _ => (self.collect)(),
+        };
+        self.scrapes = self.scrapes.wrapping_add(1);
+
+        self.cached = match result {
+            Ok(procfs) => Some(procfs),
+            Err(err) => {
+                log::warn!("Error reading procfs infos: {err:?}");
+                None
+            }
+        };
+        self.cached.clone()
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl Default for ProcFsCache {
+    fn default() -> Self {
+        Self::new(1)
+    }
+}
+
 /// Structure for holding /procfs metrics, that can be easily populated in metrics API.
+///
+/// Values are read for the whole thread group of this process from `/proc/self`, except for
+/// `thread_count` which counts the entries of `/proc/self/task`.
 #[cfg(target_os = "linux")]
+#[derive(Debug, Clone)]
 struct ProcFsMetrics {
     thread_count: usize,
     mmap_count: usize,
@@ -819,6 +2532,15 @@ struct ProcFsMetrics {
     major_page_faults: u64,
     minor_children_page_faults: u64,
     major_children_page_faults: u64,
+    cpu_user_seconds: f64,
+    cpu_system_seconds: f64,
+    start_time_seconds: f64,
+    uptime_seconds: f64,
+    voluntary_ctxt_switches: Option<u64>,
+    nonvoluntary_ctxt_switches: Option<u64>,
+    /// Bytes read from and written to storage, `None` if `/proc/self/io` is unreadable.
+    io_read_bytes: Option<u64>,
+    io_write_bytes: Option<u64>,
 }
 
 #[cfg(target_os = "linux")]
@@ -826,13 +2548,33 @@ impl ProcFsMetrics {
     /// Collect metrics from /procfs.
     #[cfg(target_os = "linux")]
     fn collect() -> Result<Self, procfs::ProcError> {
-        use procfs::process::{LimitValue, Process};
+        let current_process = procfs::process::Process::myself()?;
+        let thread_count = current_process.tasks()?.flatten().count();
+        let mmap_count = current_process.maps()?.len();
+        Self::collect_with_counts(&current_process, thread_count, mmap_count)
+    }
+
+    /// Collect metrics from /procfs again, reusing the thread and mmap counts of these metrics.
+    ///
+    /// Counting them is the most expensive part of the collection, proportional to the number of
+    /// threads and mmaps.
+    fn refresh(&self) -> Result<Self, procfs::ProcError> {
+        let current_process = procfs::process::Process::myself()?;
+        Self::collect_with_counts(&current_process, self.thread_count, self.mmap_count)
+    }
 
-        let current_process = Process::myself()?;
+    fn collect_with_counts(
+        current_process: &procfs::process::Process,
+        thread_count: usize,
+        mmap_count: usize,
+    ) -> Result<Self, procfs::ProcError> {
+        use procfs::process::LimitValue;
 
-        let thread_count = current_process.tasks()?.flatten().count();
         let stat = current_process.stat()?;
         let limits = current_process.limits()?;
+        let status = current_process.status()?;
+        // Reading I/O statistics requires ptrace access, which may be restricted
+        let io = current_process.io().ok();
 
         fn format_limit(limit: LimitValue) -> u64 {
             match limit {
@@ -844,9 +2586,20 @@ impl ProcFsMetrics {
         let max_fds_soft = format_limit(limits.max_open_files.soft_limit);
         let max_fds_hard = format_limit(limits.max_open_files.hard_limit);
 
+        // CPU times in stat are given in clock ticks
+        let ticks_per_second = procfs::ticks_per_second() as f64;
+
+        // Process start time is given in clock ticks since system boot
+        let boot_time_seconds = procfs::boot_time_secs()? as f64;
+        let start_time_seconds = boot_time_seconds + stat.starttime as f64 / ticks_per_second;
+        let now_seconds = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs_f64();
+
         Ok(Self {
             thread_count,
-            mmap_count: current_process.maps()?.len(),
+            mmap_count,
             system_mmap_limit: procfs::sys::vm::max_map_count()?,
             open_fds: current_process.fd_count()?,
             max_fds_soft,
@@ -855,13 +2608,26 @@ impl ProcFsMetrics {
             major_page_faults: stat.majflt,
             minor_children_page_faults: stat.cminflt,
             major_children_page_faults: stat.cmajflt,
+            cpu_user_seconds: stat.utime as f64 / ticks_per_second,
+            cpu_system_seconds: stat.stime as f64 / ticks_per_second,
+            start_time_seconds,
+            uptime_seconds: (now_seconds - start_time_seconds).max(0.0),
+            voluntary_ctxt_switches: status.voluntary_ctxt_switches,
+            nonvoluntary_ctxt_switches: status.nonvoluntary_ctxt_switches,
+            io_read_bytes: io.as_ref().map(|io| io.read_bytes),
+            io_write_bytes: io.as_ref().map(|io| io.write_bytes),
         })
     }
 }
 
 #[cfg(target_os = "linux")]
 impl MetricsProvider for ProcFsMetrics {
-    fn add_metrics(&self, metrics: &mut Vec<MetricFamily>, prefix: Option<&str>) {
+    fn add_metrics(
+        &self,
+        metrics: &mut Vec<MetricFamily>,
+        prefix: Option<&str>,
+        _config: &MetricsConfig,
+    ) {
         metrics.push(metric_family(
             "process_threads",
             "count of active threads",
@@ -938,11 +2704,356 @@ impl MetricsProvider for ProcFsMetrics {
             vec![counter(self.major_children_page_faults as f64, &[])],
             prefix,
         ));
+
+        metrics.push(metric_family(
+            "procfs_cpu_user_seconds_total",
+            "total CPU time spent in user mode",
+            MetricType::COUNTER,
+            vec![counter(self.cpu_user_seconds, &[])],
+            prefix,
+        ));
+
+        metrics.push(metric_family(
+            "procfs_cpu_system_seconds_total",
+            "total CPU time spent in kernel mode",
+            MetricType::COUNTER,
+            vec![counter(self.cpu_system_seconds, &[])],
+            prefix,
+        ));
+
+        metrics.push(metric_family(
+            "procfs_start_time_seconds",
+            "start time of the process since unix epoch in seconds",
+            MetricType::GAUGE,
+            vec![gauge(self.start_time_seconds, &[])],
+            prefix,
+        ));
+
+        metrics.push(metric_family(
+            "procfs_uptime_seconds",
+            "time elapsed since the process started in seconds",
+            MetricType::GAUGE,
+            vec![gauge(self.uptime_seconds, &[])],
+            prefix,
+        ));
+
+        metrics.push(metric_family(
+            "procfs_threads",
+            "count of active threads, same as process_threads but named like the other procfs metrics",
+            MetricType::GAUGE,
+            vec![gauge(self.thread_count as f64, &[])],
+            prefix,
+        ));
+
+        if let Some(voluntary_ctxt_switches) = self.voluntary_ctxt_switches {
+            metrics.push(metric_family(
+                "procfs_voluntary_ctxt_switches_total",
+                "number of voluntary context switches, such as waiting for I/O or locks",
+                MetricType::COUNTER,
+                vec![counter(voluntary_ctxt_switches as f64, &[])],
+                prefix,
+            ));
+        }
+
+        if let Some(nonvoluntary_ctxt_switches) = self.nonvoluntary_ctxt_switches {
+            metrics.push(metric_family(
+                "procfs_nonvoluntary_ctxt_switches_total",
+                "number of involuntary context switches, due to preemption by the scheduler",
+                MetricType::COUNTER,
+                vec![counter(nonvoluntary_ctxt_switches as f64, &[])],
+                prefix,
+            ));
+        }
+
+        if let Some(io_read_bytes) = self.io_read_bytes {
+            metrics.push(metric_family(
+                "procfs_io_read_bytes_total",
+                "number of bytes read from storage, excluding reads served from page cache",
+                MetricType::COUNTER,
+                vec![counter(io_read_bytes as f64, &[])],
+                prefix,
+            ));
+        }
+
+        if let Some(io_write_bytes) = self.io_write_bytes {
+            metrics.push(metric_family(
+                "procfs_io_write_bytes_total",
+                "number of bytes caused to be written to storage",
+                MetricType::COUNTER,
+                vec![counter(io_write_bytes as f64, &[])],
+                prefix,
+            ));
+        }
+    }
+}
+
+/// Mount point of the cgroup v2 hierarchy. Inside a container with its own cgroup namespace, this
+/// is the cgroup of the container.
+#[cfg(target_os = "linux")]
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// Structure for holding cgroup v2 limits and usage, which constrain qdrant in containers rather
+/// than the limits of the host.
+///
+/// Limits are `None` if unlimited or unreadable.
+#[cfg(target_os = "linux")]
+#[derive(Debug, Clone)]
+struct CgroupMetrics {
+    memory_limit_bytes: Option<u64>,
+    memory_current_bytes: Option<u64>,
+    cpu_quota: Option<f64>,
+}
+
+#[cfg(target_os = "linux")]
+impl CgroupMetrics {
+    /// Collect metrics from the cgroup v2 hierarchy, if mounted.
+    fn collect() -> Option<Self> {
+        Self::collect_from(Path::new(CGROUP_ROOT))
+    }
+
+    fn collect_from(root: &Path) -> Option<Self> {
+        // Only the cgroup v2 unified hierarchy has this file at its root
+        if !root.join("cgroup.controllers").is_file() {
+            return None;
+        }
+
+        let read = |file: &str| fs_err::read_to_string(root.join(file)).ok();
+
+        Some(Self {
+            memory_limit_bytes: read("memory.max").and_then(|max| max.trim().parse().ok()),
+            memory_current_bytes: read("memory.current")
+                .and_then(|current| current.trim().parse().ok()),
+            cpu_quota: read("cpu.max").and_then(|max| Self::parse_cpu_max(&max)),
+        })
+    }
+
+    /// Parse `cpu.max`, formatted as `$MAX $PERIOD`, into the number of CPUs allowed.
+    fn parse_cpu_max(cpu_max: &str) -> Option<f64> {
+        let (max, period) = cpu_max.trim().split_once(' ')?;
+        // `max` means unlimited
+        let max: f64 = max.parse().ok()?;
+        let period: f64 = period.parse().ok()?;
+        (period > 0.0).then(|| max / period)
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl MetricsProvider for CgroupMetrics {
+    fn add_metrics(
+        &self,
+        metrics: &mut Vec<MetricFamily>,
+        prefix: Option<&str>,
+        _config: &MetricsConfig,
+    ) {
+        if let Some(memory_limit_bytes) = self.memory_limit_bytes {
+            metrics.push(metric_family(
+                "cgroup_memory_limit_bytes",
+                "memory limit of the cgroup",
+                MetricType::GAUGE,
+                vec![gauge(memory_limit_bytes as f64, &[])],
+                prefix,
+            ));
+        }
+
+        if let Some(memory_current_bytes) = self.memory_current_bytes {
+            metrics.push(metric_family(
+                "cgroup_memory_current_bytes",
+                "memory currently used by the cgroup, including page cache",
+                MetricType::GAUGE,
+                vec![gauge(memory_current_bytes as f64, &[])],
+                prefix,
+            ));
+        }
+
+        if let Some(cpu_quota) = self.cpu_quota {
+            metrics.push(metric_family(
+                "cgroup_cpu_quota",
+                "number of CPUs the cgroup may use",
+                MetricType::GAUGE,
+                vec![gauge(cpu_quota, &[])],
+                prefix,
+            ));
+        }
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use std::collections::HashMap;
+    use std::num::NonZeroU32;
+    use std::sync::Arc;
+    use std::sync::atomic::Ordering;
+    use std::time::{Duration, Instant};
+
+    use api::rest::models::HardwareUsage;
+    use chrono::Utc;
+    use collection::collection_manager::optimizers::{TrackerStatus, TrackerTelemetry};
+    use collection::config::{CollectionParams, WalConfig};
+    use collection::operations::config_diff::HnswConfigDiff;
+    use collection::operations::types::{Datatype, ShardTransferInfo, VectorsConfig};
+    use collection::operations::vector_params_builder::VectorParamsBuilder;
+    use collection::optimizers_builder::OptimizersConfig;
+    use collection::shards::replica_set::ReplicaState;
+    use collection::shards::shard::ShardId;
+    use collection::shards::telemetry::{LocalShardTelemetry, ReplicaSetTelemetry};
+    use collection::telemetry::{CollectionConfigTelemetry, CollectionTelemetry};
+    use prometheus::proto::{MetricFamily, MetricType};
+    use regex::Regex;
+    use sealed_test::prelude::*;
+    use segment::common::operation_time_statistics::OperationDurationStatistics;
+    use segment::telemetry::SegmentTelemetry;
+    use segment::types::{
+        BinaryQuantization, BinaryQuantizationConfig, Distance, HnswConfig, PayloadIndexInfo,
+        PayloadSchemaType, QuantizationConfig, ScalarQuantization, ScalarQuantizationConfig,
+        ScalarType, SegmentConfig, SegmentInfo, SegmentType, ShardKey, VectorDataInfo,
+    };
+    use storage::types::ConsensusThreadStatus;
+
+    use super::{
+        CollectionsMetrics, METRICS_COLLECTION_ERRORS, MetricsBuilder, MetricsData,
+        MetricsProvider, TelemetryMetrics, add_metrics_catch_unwind,
+    };
+    use crate::common::telemetry::TelemetryData;
+    use crate::common::telemetry_ops::app_telemetry::{AppBuildTelemetry, AppFeaturesTelemetry};
+    use crate::common::telemetry_ops::cluster_telemetry::{
+        ClusterStatusTelemetry, ClusterTelemetry,
+    };
+    use crate::common::telemetry_ops::collections_telemetry::{
+        CollectionTelemetryEnum, CollectionsTelemetry,
+    };
+    use crate::common::telemetry_ops::hardware::HardwareTelemetry;
+    use crate::common::telemetry_ops::memory_telemetry::MemoryTelemetry;
+    use crate::common::telemetry_ops::requests_telemetry::{
+        GRPC_CODE_UNKNOWN, GrpcTelemetry, RequestsTelemetry, WebApiTelemetry,
+    };
+    use crate::settings::{MetricsConfig, MetricsDurationUnit};
+
+    /// Local shard telemetry without any data.
+    fn local_shard() -> LocalShardTelemetry {
+        LocalShardTelemetry {
+            variant_name: None,
+            status: None,
+            total_optimized_points: 0,
+            vectors_size_bytes: None,
+            payloads_size_bytes: None,
+            num_points: None,
+            num_vectors: None,
+            num_vectors_by_name: None,
+            segments: None,
+            optimizations: Default::default(),
+            async_scorer: None,
+            indexed_only_excluded_vectors: None,
+        }
+    }
+
+    /// Replica set with a single active replica, holding the given local shard.
+    fn replica_set(id: ShardId, local: LocalShardTelemetry) -> ReplicaSetTelemetry {
+        ReplicaSetTelemetry {
+            id,
+            key: None,
+            local: Some(local),
+            remote: vec![],
+            replicate_states: HashMap::from([(1, ReplicaState::Active)]),
+            partial_snapshot: None,
+        }
+    }
+
+    /// Collection telemetry with default configuration and the given shards.
+    fn collection(id: &str, shards: Vec<ReplicaSetTelemetry>) -> CollectionTelemetry {
+        CollectionTelemetry {
+            id: id.to_string(),
+            init_time_ms: 0,
+            config: CollectionConfigTelemetry {
+                params: CollectionParams::empty(),
+                hnsw_config: HnswConfig::default(),
+                optimizer_config: OptimizersConfig {
+                    deleted_threshold: 0.2,
+                    vacuum_min_vector_number: 1000,
+                    default_segment_number: 0,
+                    max_segment_size: None,
+                    #[expect(deprecated)]
+                    memmap_threshold: None,
+                    indexing_threshold: None,
+                    flush_interval_sec: 5,
+                    max_optimization_threads: None,
+                },
+                wal_config: WalConfig::default(),
+                quantization_config: None,
+                strict_mode_config: None,
+                uuid: None,
+                metadata: None,
+            },
+            shards: Some(shards),
+            transfers: None,
+            resharding: None,
+            shard_clean_tasks: None,
+        }
+    }
+
+    /// Segment info without any data.
+    fn segment_info() -> SegmentInfo {
+        SegmentInfo {
+            segment_type: SegmentType::Plain,
+            num_vectors: 0,
+            num_points: 0,
+            num_indexed_vectors: 0,
+            num_deleted_vectors: 0,
+            vectors_size_bytes: 0,
+            payloads_size_bytes: 0,
+            ram_usage_bytes: 0,
+            disk_usage_bytes: 0,
+            is_appendable: true,
+            index_schema: HashMap::new(),
+            vector_data: HashMap::new(),
+        }
+    }
+
+    /// Segment telemetry with the given segment info.
+    fn segment(info: SegmentInfo) -> SegmentTelemetry {
+        SegmentTelemetry {
+            info,
+            config: SegmentConfig::default(),
+            vector_index_searches: vec![],
+            payload_field_indices: vec![],
+        }
+    }
+
+    /// Collections telemetry for the given full collection telemetries.
+    fn collections(collections: Vec<CollectionTelemetry>) -> CollectionsTelemetry {
+        CollectionsTelemetry {
+            number_of_collections: collections.len(),
+            max_collections: None,
+            collections: Some(
+                collections
+                    .into_iter()
+                    .map(|collection| CollectionTelemetryEnum::Full(Box::new(collection)))
+                    .collect(),
+            ),
+        }
+    }
+
+    /// Find a metric family by its full name, panicking if it is missing.
+    fn find_family<'a>(metrics: &'a [MetricFamily], name: &str) -> &'a MetricFamily {
+        metrics
+            .iter()
+            .find(|family| family.name() == name)
+            .unwrap_or_else(|| panic!("metric family {name} is missing"))
+    }
+
+    /// Value of the first gauge in the given metric family.
+    fn gauge_value(metrics: &[MetricFamily], name: &str) -> f64 {
+        find_family(metrics, name).get_metric()[0]
+            .get_gauge()
+            .get_value()
+    }
+
+    /// Value of the first counter in the given metric family.
+    fn counter_value(metrics: &[MetricFamily], name: &str) -> f64 {
+        find_family(metrics, name).get_metric()[0]
+            .get_counter()
+            .get_value()
+    }
+
     #[test]
     fn test_endpoint_whitelists_sorted() {
         use super::{GRPC_ENDPOINT_WHITELIST, REST_ENDPOINT_WHITELIST};
@@ -956,4 +3067,2100 @@ mod tests {
             "GRPC_ENDPOINT_WHITELIST must be sorted in code to allow binary search"
         );
     }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_procfs_cpu_seconds() {
+        use super::ProcFsMetrics;
+
+        let cpu_seconds = || {
+            let mut metrics = vec![];
+            ProcFsMetrics::collect().unwrap().add_metrics(
+                &mut metrics,
+                None,
+                &MetricsConfig::default(),
+            );
+            (
+                counter_value(&metrics, "procfs_cpu_user_seconds_total"),
+                counter_value(&metrics, "procfs_cpu_system_seconds_total"),
+            )
+        };
+
+        let (user_before, system_before) = cpu_seconds();
+        let (user_after, system_after) = cpu_seconds();
+
+        assert!(user_before >= 0.0 && system_before >= 0.0);
+        assert!(user_after >= user_before);
+        assert!(system_after >= system_before);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_procfs_start_time_and_uptime() {
+        use super::ProcFsMetrics;
+
+        let mut metrics = vec![];
+        ProcFsMetrics::collect().unwrap().add_metrics(
+            &mut metrics,
+            None,
+            &MetricsConfig::default(),
+        );
+
+        // Somewhere after 2020-01-01, and not in the future
+        let start_time = gauge_value(&metrics, "procfs_start_time_seconds");
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_secs_f64();
+        assert!(start_time > 1_577_836_800.0);
+        assert!(start_time <= now + 1.0);
+
+        assert!(gauge_value(&metrics, "procfs_uptime_seconds") > 0.0);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_procfs_threads() {
+        use super::ProcFsMetrics;
+
+        let mut metrics = vec![];
+        ProcFsMetrics::collect().unwrap().add_metrics(
+            &mut metrics,
+            None,
+            &MetricsConfig::default(),
+        );
+
+        assert!(gauge_value(&metrics, "procfs_threads") >= 1.0);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_procfs_ctxt_switches() {
+        use super::ProcFsMetrics;
+
+        let ctxt_switches = || {
+            let mut metrics = vec![];
+            ProcFsMetrics::collect().unwrap().add_metrics(
+                &mut metrics,
+                None,
+                &MetricsConfig::default(),
+            );
+            (
+                counter_value(&metrics, "procfs_voluntary_ctxt_switches_total"),
+                counter_value(&metrics, "procfs_nonvoluntary_ctxt_switches_total"),
+            )
+        };
+
+        let (voluntary_before, nonvoluntary_before) = ctxt_switches();
+        let (voluntary_after, nonvoluntary_after) = ctxt_switches();
+
+        assert!(voluntary_after >= voluntary_before);
+        assert!(nonvoluntary_after >= nonvoluntary_before);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_procfs_io() {
+        use super::ProcFsMetrics;
+
+        let mut procfs = ProcFsMetrics::collect().unwrap();
+        let has_io = procfs.io_read_bytes.is_some();
+
+        let mut metrics = vec![];
+        procfs.add_metrics(&mut metrics, None, &MetricsConfig::default());
+        let io_families = metrics
+            .iter()
+            .filter(|family| family.name().starts_with("procfs_io_"))
+            .count();
+        assert_eq!(io_families, if has_io { 2 } else { 0 });
+
+        // Skipped if `/proc/self/io` is unreadable
+        procfs.io_read_bytes = None;
+        procfs.io_write_bytes = None;
+        let mut metrics = vec![];
+        procfs.add_metrics(&mut metrics, None, &MetricsConfig::default());
+        assert!(
+            metrics
+                .iter()
+                .all(|family| !family.name().starts_with("procfs_io_"))
+        );
+    }
+
+    #[test]
+    fn test_indexed_only_excluded_threshold() {
+        let with_excluded = |id: &str, excluded: usize| {
+            let mut local = local_shard();
+            local.indexed_only_excluded_vectors = Some(HashMap::from([("".to_string(), excluded)]));
+            collection(id, vec![replica_set(0, local)])
+        };
+        let telemetry = collections(vec![
+            with_excluded("none", 0),
+            with_excluded("few", 5),
+            with_excluded("many", 500),
+        ]);
+
+        let config = MetricsConfig {
+            indexed_only_excluded_threshold: Some(100),
+            ..Default::default()
+        };
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &config);
+
+        let excluded = find_family(&metrics, "collection_indexed_only_excluded_points");
+        assert_eq!(excluded.get_metric().len(), 1);
+        assert_eq!(excluded.get_metric()[0].get_label()[0].value(), "many");
+    }
+
+    #[test]
+    fn test_collection_shard_key_ranges() {
+        let shards = (0..8)
+            .map(|shard_id| {
+                let mut shard = replica_set(shard_id, local_shard());
+                shard.key = Some(ShardKey::Number(u64::from(shard_id)));
+                shard
+            })
+            .collect();
+        let telemetry = collections(vec![
+            collection("custom", shards),
+            collection("auto", vec![replica_set(0, local_shard())]),
+        ]);
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+
+        let shard_keys = find_family(&metrics, "collection_shard_key_ranges");
+        assert_eq!(shard_keys.get_metric().len(), 1);
+        assert_eq!(shard_keys.get_metric()[0].get_label()[0].value(), "custom");
+        assert_eq!(shard_keys.get_metric()[0].get_gauge().get_value(), 8.0);
+    }
+
+    #[test]
+    fn test_grpc_responses_by_code() {
+        let endpoint = "/qdrant.Points/Search".to_string();
+        let telemetry = GrpcTelemetry {
+            responses: HashMap::from([(endpoint.clone(), Default::default())]),
+            responses_by_code: HashMap::from([(
+                endpoint,
+                HashMap::from([
+                    ("OK".to_string(), 10),
+                    ("INTERNAL".to_string(), 2),
+                    (GRPC_CODE_UNKNOWN.to_string(), 5),
+                ]),
+            )]),
+        };
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+
+        let by_code = find_family(&metrics, "grpc_responses_by_code_total");
+        let mut series = by_code
+            .get_metric()
+            .iter()
+            .map(|metric| {
+                (
+                    metric.get_label()[1].value().to_string(),
+                    metric.get_counter().get_value(),
+                )
+            })
+            .collect::<Vec<_>>();
+        series.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            series,
+            vec![
+                ("INTERNAL".to_string(), 2.0),
+                ("OK".to_string(), 10.0),
+                ("unknown".to_string(), 5.0),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_memory_quota_utilization() {
+        const GIB: usize = 1024 * 1024 * 1024;
+
+        let telemetry = MemoryTelemetry {
+            resident_bytes: 8 * GIB,
+            ..Default::default()
+        };
+        let config = MetricsConfig {
+            memory_quota_bytes: Some(16 * GIB),
+            ..Default::default()
+        };
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &config);
+
+        assert_eq!(
+            gauge_value(&metrics, "node_memory_quota_bytes"),
+            (16 * GIB) as f64,
+        );
+        assert_eq!(gauge_value(&metrics, "node_memory_quota_utilization"), 0.5);
+    }
+
+    #[test]
+    fn test_collection_group_points() {
+        let with_points = |id: &str, points: usize| {
+            let mut local = local_shard();
+            local.num_points = Some(points);
+            collection(id, vec![replica_set(0, local)])
+        };
+        let telemetry = collections(vec![
+            with_points("prod-a", 10),
+            with_points("prod-b", 20),
+            with_points("staging-c", 5),
+            with_points("other", 100),
+        ]);
+        let group_regex = Regex::new("^(prod|staging)-").unwrap();
+
+        let mut metrics = vec![];
+        CollectionsMetrics {
+            telemetry: &telemetry,
+            group_regex: Some(&group_regex),
+        }
+        .add_metrics(&mut metrics, None, &MetricsConfig::default());
+
+        let groups = find_family(&metrics, "collection_group_points");
+        let points_of = |group: &str| {
+            groups
+                .get_metric()
+                .iter()
+                .find(|metric| metric.get_label()[0].value() == group)
+                .map(|metric| metric.get_gauge().get_value())
+        };
+        assert_eq!(groups.get_metric().len(), 2);
+        assert_eq!(points_of("prod"), Some(30.0));
+        assert_eq!(points_of("staging"), Some(5.0));
+
+        // Per-collection series are still reported
+        assert_eq!(
+            find_family(&metrics, "collection_points")
+                .get_metric()
+                .len(),
+            4,
+        );
+    }
+
+    #[test]
+    fn test_collection_payload_indexes() {
+        let with_indexes = |fields: &[&str]| {
+            let mut info = segment_info();
+            info.index_schema = fields
+                .iter()
+                .map(|field| {
+                    let index_info = PayloadIndexInfo {
+                        data_type: PayloadSchemaType::Keyword,
+                        params: None,
+                        points: 0,
+                    };
+                    (field.parse().unwrap(), index_info)
+                })
+                .collect();
+            segment(info)
+        };
+
+        // Fields indexed in multiple segments must only be counted once
+        let mut local = local_shard();
+        local.segments = Some(vec![
+            with_indexes(&["a", "b", "c"]),
+            with_indexes(&["a", "b", "d"]),
+        ]);
+        let telemetry = collections(vec![collection("indexed", vec![replica_set(0, local)])]);
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+
+        assert_eq!(gauge_value(&metrics, "collection_payload_indexes"), 4.0);
+    }
+
+    #[test]
+    fn test_metrics_builder_reuses_family_list() {
+        let telemetry = collections(vec![
+            collection("first", vec![replica_set(0, local_shard())]),
+            collection("second", vec![replica_set(0, local_shard())]),
+        ]);
+
+        let mut builder = MetricsBuilder::new(Some("test_"), &MetricsConfig::default());
+
+        builder.update_from(&telemetry);
+        let snapshot = builder.snapshot();
+        let first = snapshot.format_metrics();
+
+        // Snapshots share the metric families instead of copying them
+        assert!(Arc::ptr_eq(&snapshot.metrics, &builder.data.metrics));
+
+        // Updating while a snapshot is in use must not change the snapshot
+        builder.update_from(&telemetry);
+        assert!(!Arc::ptr_eq(&snapshot.metrics, &builder.data.metrics));
+        assert_eq!(snapshot.format_metrics(), first);
+        drop(snapshot);
+
+        // Spare capacity is only kept if the list of families is reused, a new list would not
+        // have it
+        let families = Arc::get_mut(&mut builder.data.metrics).unwrap();
+        families.reserve(1000);
+        let capacity = families.capacity();
+        builder.update_from(&telemetry);
+        assert_eq!(builder.data.metrics.capacity(), capacity);
+
+        // Updating with the same telemetry must produce the same output
+        assert_eq!(builder.snapshot().format_metrics(), first);
+    }
+
+    /// Metric families emitted for the fixture in [`test_metric_family_names`].
+    ///
+    /// Renaming, adding or removing a metric must be reflected here deliberately.
+    const EXPECTED_FAMILY_NAMES: &[&str] = &[
+        "active_replicas_max",
+        "active_replicas_min",
+        "app_build_info",
+        "app_feature_enabled",
+        "app_info",
+        "app_log_level",
+        "app_status_recovery_mode",
+        "cluster_commit",
+        "cluster_enabled",
+        "cluster_leader",
+        "cluster_peers_total",
+        "cluster_pending_operations_total",
+        "cluster_term",
+        "cluster_voter",
+        "collection_deleted_ratio",
+        "collection_deleted_vectors",
+        "collection_group_points",
+        "collection_hardware_metric_cpu",
+        "collection_hardware_metric_payload_index_io_read",
+        "collection_hardware_metric_payload_index_io_write",
+        "collection_hardware_metric_payload_io_read",
+        "collection_hardware_metric_payload_io_write",
+        "collection_hardware_metric_vector_io_read",
+        "collection_hardware_metric_vector_io_write",
+        "collection_hnsw_on_disk",
+        "collection_indexed_only_excluded_points",
+        "collection_indexed_points",
+        "collection_payload_indexes",
+        "collection_points",
+        "collection_quantization",
+        "collection_recent_optimizer_failures",
+        "collection_replication_health_ratio",
+        "collection_segment_size_avg_bytes",
+        "collection_segment_size_max_bytes",
+        "collection_segment_size_min_bytes",
+        "collection_segments",
+        "collection_segments_optimizing",
+        "collection_serving_replicas",
+        "collection_shard_key_ranges",
+        "collection_shards_total",
+        "collection_target_segments",
+        "collection_vector_coverage_ratio",
+        "collection_vector_datatype",
+        "collection_vectors",
+        "collections_total",
+        "dead_replicas",
+        "grpc_responses_avg_duration_seconds",
+        "grpc_responses_by_code_total",
+        "grpc_responses_duration_seconds",
+        "grpc_responses_fail_total",
+        "grpc_responses_max_duration_seconds",
+        "grpc_responses_min_duration_seconds",
+        "grpc_responses_per_second",
+        "grpc_responses_total",
+        "large_collections_total",
+        "memory_active_bytes",
+        "memory_allocated_bytes",
+        "memory_metadata_bytes",
+        "memory_resident_bytes",
+        "memory_retained_bytes",
+        "metrics_collection_errors_total",
+        "metrics_payload_bytes",
+        "node_memory_quota_bytes",
+        "node_memory_quota_utilization",
+        "optimizer_running_processes",
+        "points_total",
+        "process_restart_epoch",
+        "rest_endpoint_info",
+        "rest_responses_avg_duration_seconds",
+        "rest_responses_duration_seconds",
+        "rest_responses_fail_total",
+        "rest_responses_max_duration_seconds",
+        "rest_responses_min_duration_seconds",
+        "rest_responses_per_second",
+        "rest_responses_total",
+        "shard_transfers_active",
+        "storage_disk_free_bytes",
+        "storage_disk_total_bytes",
+        "vectors_total",
+    ];
+
+    /// Procfs and cgroup metric families additionally emitted on Linux.
+    #[cfg(target_os = "linux")]
+    const EXPECTED_LINUX_FAMILY_NAMES: &[&str] = &[
+        "cgroup_cpu_quota",
+        "cgroup_memory_current_bytes",
+        "cgroup_memory_limit_bytes",
+        "process_major_page_faults_children_total",
+        "process_major_page_faults_total",
+        "process_max_fds",
+        "process_minor_page_faults_children_total",
+        "process_minor_page_faults_total",
+        "process_open_fds",
+        "process_open_mmaps",
+        "process_threads",
+        "procfs_cpu_system_seconds_total",
+        "procfs_cpu_user_seconds_total",
+        "procfs_io_read_bytes_total",
+        "procfs_io_write_bytes_total",
+        "procfs_nonvoluntary_ctxt_switches_total",
+        "procfs_start_time_seconds",
+        "procfs_threads",
+        "procfs_uptime_seconds",
+        "procfs_voluntary_ctxt_switches_total",
+        "system_max_mmaps",
+    ];
+
+    #[test]
+    fn test_metric_family_names() {
+        let mut local = local_shard();
+        local.num_points = Some(10);
+        local.num_vectors_by_name = Some(HashMap::from([("text".to_string(), 10)]));
+        local.indexed_only_excluded_vectors = Some(HashMap::from([("text".to_string(), 5)]));
+        local.segments = Some(vec![segment({
+            let mut info = segment_info();
+            info.index_schema = HashMap::from([(
+                "city".parse().unwrap(),
+                PayloadIndexInfo {
+                    data_type: PayloadSchemaType::Keyword,
+                    params: None,
+                    points: 10,
+                },
+            )]);
+            info
+        })]);
+        let mut shard = replica_set(0, local);
+        shard.key = Some(ShardKey::Number(1));
+        let mut collection = collection("prod-docs", vec![shard]);
+        collection.config.params.vectors = VectorsConfig::Multi(
+            [(
+                "text".to_string(),
+                VectorParamsBuilder::new(384, Distance::Cosine).build(),
+            )]
+            .into(),
+        );
+
+        let rest = WebApiTelemetry {
+            responses: HashMap::from([(
+                "POST /collections/{name}/points/search/matrix/offsets".to_string(),
+                HashMap::from([(200, Default::default())]),
+            )]),
+        };
+        let endpoint = "/qdrant.Points/Search".to_string();
+        let grpc = GrpcTelemetry {
+            responses: HashMap::from([(endpoint.clone(), Default::default())]),
+            responses_by_code: HashMap::from([(endpoint, HashMap::from([("OK".to_string(), 1)]))]),
+        };
+
+        let telemetry = TelemetryData {
+            id: "test".to_string(),
+            app: AppBuildTelemetry {
+                name: "qdrant".to_string(),
+                version: "1.0.0".to_string(),
+                features: Some(AppFeaturesTelemetry {
+                    debug: false,
+                    service_debug_feature: false,
+                    recovery_mode: false,
+                    gpu: false,
+                    rocksdb: false,
+                }),
+                runtime_features: None,
+                hnsw_global_config: None,
+                system: None,
+                jwt_rbac: None,
+                hide_jwt_dashboard: None,
+                startup: Utc::now(),
+            },
+            collections: collections(vec![collection]),
+            cluster: Some(ClusterTelemetry {
+                enabled: true,
+                status: Some(ClusterStatusTelemetry {
+                    number_of_peers: 3,
+                    term: 2,
+                    commit: 10,
+                    pending_operations: 0,
+                    role: None,
+                    is_voter: true,
+                    peer_id: Some(1),
+                    leader: Some(2),
+                    consensus_thread_status: ConsensusThreadStatus::Stopped,
+                }),
+                config: None,
+                peers: None,
+                peer_metadata: None,
+                metadata: None,
+            }),
+            requests: Some(RequestsTelemetry { rest, grpc }),
+            memory: Some(MemoryTelemetry::default()),
+            hardware: Some(HardwareTelemetry {
+                collection_data: HashMap::from([(
+                    "prod-docs".to_string(),
+                    HardwareUsage {
+                        cpu: 10,
+                        ..Default::default()
+                    },
+                )]),
+            }),
+        };
+
+        // Enable all optional metric families
+        let config = MetricsConfig {
+            memory_quota_bytes: Some(1024),
+            collection_group_pattern: Some("^([a-z]+)-".to_string()),
+            large_collection_points_threshold: Some(5),
+            aggregate_collection_metrics: true,
+            restart_epoch: true,
+            payload_size: true,
+            compact_endpoint_labels: true,
+            response_rates: true,
+            ..Default::default()
+        };
+        let dir = tempfile::tempdir().unwrap();
+        let mut builder = MetricsBuilder::new(None, &config).with_storage_path(dir.path());
+
+        // Collection groups are reported with the regex compiled by the builder
+        let collection_group_regex = builder.collection_group_regex.clone();
+        let telemetry = TelemetryMetrics {
+            telemetry: &telemetry,
+            collection_group_regex: collection_group_regex.as_ref(),
+        };
+
+        // Deterministic procfs and cgroup metrics, with all optional metrics available
+        #[cfg(target_os = "linux")]
+        let provider = {
+            use super::{CgroupMetrics, ProcFsMetrics, WithProcFs};
+
+            WithProcFs {
+                provider: &telemetry,
+                procfs: Some(ProcFsMetrics {
+                    thread_count: 8,
+                    mmap_count: 100,
+                    system_mmap_limit: 65530,
+                    open_fds: 20,
+                    max_fds_soft: 1024,
+                    max_fds_hard: 4096,
+                    minor_page_faults: 1,
+                    major_page_faults: 1,
+                    minor_children_page_faults: 1,
+                    major_children_page_faults: 1,
+                    cpu_user_seconds: 1.0,
+                    cpu_system_seconds: 1.0,
+                    start_time_seconds: 1_760_000_000.0,
+                    uptime_seconds: 60.0,
+                    voluntary_ctxt_switches: Some(1),
+                    nonvoluntary_ctxt_switches: Some(1),
+                    io_read_bytes: Some(1),
+                    io_write_bytes: Some(1),
+                }),
+                cgroup: Some(CgroupMetrics {
+                    memory_limit_bytes: Some(1 << 30),
+                    memory_current_bytes: Some(1 << 20),
+                    cpu_quota: Some(2.0),
+                }),
+            }
+        };
+        #[cfg(not(target_os = "linux"))]
+        let provider = telemetry;
+
+        // Response rates are only known from the second scrape
+        let first_scrape = Instant::now();
+        builder.update_from_at(&provider, first_scrape);
+        builder.update_from_at(&provider, first_scrape + Duration::from_secs(10));
+
+        let mut expected = EXPECTED_FAMILY_NAMES.to_vec();
+        #[cfg(target_os = "linux")]
+        expected.extend_from_slice(EXPECTED_LINUX_FAMILY_NAMES);
+        expected.sort_unstable();
+        assert!(expected.windows(2).all(|names| names[0] != names[1]));
+
+        // Every family must be emitted exactly once, Prometheus rejects duplicate families
+        assert_eq!(builder.snapshot().family_names(), expected);
+    }
+
+    #[test]
+    fn test_collection_replication_health_ratio() {
+        let mut shard = replica_set(0, local_shard());
+        shard.replicate_states = HashMap::from([
+            (1, ReplicaState::Active),
+            (2, ReplicaState::Active),
+            (3, ReplicaState::Dead),
+        ]);
+        let mut collection = collection("under_replicated", vec![shard]);
+        collection.config.params.replication_factor = NonZeroU32::new(3).unwrap();
+        let telemetry = collections(vec![collection]);
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+
+        let ratio = gauge_value(&metrics, "collection_replication_health_ratio");
+        assert!((ratio - 2.0 / 3.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_skip_zero_hardware_counters() {
+        let telemetry = HardwareTelemetry {
+            collection_data: HashMap::from([(
+                "idle".to_string(),
+                HardwareUsage {
+                    cpu: 10,
+                    ..Default::default()
+                },
+            )]),
+        };
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+        assert_eq!(
+            counter_value(&metrics, "collection_hardware_metric_vector_io_read"),
+            0.0,
+        );
+
+        let config = MetricsConfig {
+            skip_zero_hardware_counters: true,
+            ..Default::default()
+        };
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &config);
+        assert_eq!(
+            counter_value(&metrics, "collection_hardware_metric_cpu"),
+            10.0
+        );
+        assert!(
+            !metrics
+                .iter()
+                .any(|family| family.name() == "collection_hardware_metric_vector_io_read")
+        );
+    }
+
+    #[test]
+    fn test_collection_hnsw_on_disk() {
+        let on_disk = HnswConfigDiff {
+            on_disk: Some(true),
+            ..Default::default()
+        };
+        let mut collection = collection("mixed", vec![]);
+        collection.config.params.vectors = VectorsConfig::Multi(
+            [
+                (
+                    "image".to_string(),
+                    VectorParamsBuilder::new(512, Distance::Cosine)
+                        .with_hnsw_config(on_disk)
+                        .build(),
+                ),
+                (
+                    "text".to_string(),
+                    VectorParamsBuilder::new(384, Distance::Cosine).build(),
+                ),
+            ]
+            .into(),
+        );
+        let telemetry = collections(vec![collection]);
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+
+        let hnsw_on_disk = find_family(&metrics, "collection_hnsw_on_disk");
+        let mut series = hnsw_on_disk
+            .get_metric()
+            .iter()
+            .map(|metric| {
+                (
+                    metric.get_label()[1].value().to_string(),
+                    metric.get_gauge().get_value(),
+                )
+            })
+            .collect::<Vec<_>>();
+        series.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(
+            series,
+            vec![("image".to_string(), 1.0), ("text".to_string(), 0.0)],
+        );
+    }
+
+    #[test]
+    fn test_metrics_to_ndjson() {
+        let telemetry = GrpcTelemetry {
+            responses: HashMap::from([
+                ("/qdrant.Points/Search".to_string(), Default::default()),
+                ("/qdrant.Points/Scroll".to_string(), Default::default()),
+            ]),
+            responses_by_code: HashMap::new(),
+        };
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+        let metrics_data = MetricsData::from(metrics);
+
+        // Samples of each histogram are expanded into buckets, sum and count
+        let expected_samples: usize = metrics_data
+            .metrics
+            .iter()
+            .map(|family| match family.get_field_type() {
+                MetricType::HISTOGRAM => family
+                    .get_metric()
+                    .iter()
+                    .map(|metric| metric.get_histogram().get_bucket().len() + 3)
+                    .sum(),
+                _ => family.get_metric().len(),
+            })
+            .sum();
+
+        let ndjson = metrics_data.to_ndjson();
+        let samples = ndjson
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .collect::<Vec<_>>();
+
+        assert_eq!(samples.len(), expected_samples);
+        assert!(samples.iter().all(|sample| {
+            sample["name"].is_string()
+                && sample["labels"]["endpoint"].is_string()
+                && sample["value"].is_number()
+                && sample["type"].is_string()
+        }));
+    }
+
+    #[test]
+    fn test_collection_quantization() {
+        let with_quantization = |id: &str, quantization_config| {
+            let mut collection = collection(id, vec![]);
+            collection.config.quantization_config = Some(quantization_config);
+            collection
+        };
+        let telemetry = collections(vec![
+            with_quantization(
+                "scalar",
+                QuantizationConfig::Scalar(ScalarQuantization {
+                    scalar: ScalarQuantizationConfig {
+                        r#type: ScalarType::Int8,
+                        quantile: None,
+                        always_ram: None,
+                    },
+                }),
+            ),
+            with_quantization(
+                "binary",
+                QuantizationConfig::Binary(BinaryQuantization {
+                    binary: BinaryQuantizationConfig {
+                        always_ram: None,
+                        encoding: None,
+                        query_encoding: None,
+                    },
+                }),
+            ),
+        ]);
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+
+        let quantization = find_family(&metrics, "collection_quantization");
+        let series = quantization
+            .get_metric()
+            .iter()
+            .map(|metric| {
+                (
+                    metric.get_label()[0].value().to_string(),
+                    metric.get_label()[1].value().to_string(),
+                    metric.get_gauge().get_value(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            series,
+            vec![
+                ("scalar".to_string(), "scalar".to_string(), 1.0),
+                ("binary".to_string(), "binary".to_string(), 1.0),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_panicking_metrics_provider() {
+        struct PanickingProvider;
+
+        impl MetricsProvider for PanickingProvider {
+            fn add_metrics(
+                &self,
+                metrics: &mut Vec<MetricFamily>,
+                _prefix: Option<&str>,
+                _config: &MetricsConfig,
+            ) {
+                metrics.push(MetricFamily::default());
+                panic!("malformed telemetry");
+            }
+        }
+
+        let config = MetricsConfig::default();
+        let errors_before = METRICS_COLLECTION_ERRORS.load(Ordering::Relaxed);
+
+        let mut metrics = vec![];
+        add_metrics_catch_unwind(&collections(vec![]), &mut metrics, None, &config);
+        add_metrics_catch_unwind(&PanickingProvider, &mut metrics, None, &config);
+        add_metrics_catch_unwind(&MemoryTelemetry::default(), &mut metrics, None, &config);
+
+        // Metrics of the panicking provider are left out entirely
+        assert!(metrics.iter().all(|family| !family.name().is_empty()));
+        assert_eq!(gauge_value(&metrics, "collections_total"), 0.0);
+        assert_eq!(gauge_value(&metrics, "memory_resident_bytes"), 0.0);
+
+        // Other tests may fail providers concurrently, so only a lower bound is known
+        assert!(METRICS_COLLECTION_ERRORS.load(Ordering::Relaxed) > errors_before);
+    }
+
+    #[test]
+    fn test_metrics_collection_errors_same_scrape() {
+        struct PanickingProvider;
+
+        impl MetricsProvider for PanickingProvider {
+            fn add_metrics(
+                &self,
+                _metrics: &mut Vec<MetricFamily>,
+                _prefix: Option<&str>,
+                _config: &MetricsConfig,
+            ) {
+                panic!("malformed telemetry");
+            }
+        }
+
+        /// Provider with a failing part, like procfs metrics next to the telemetry.
+        struct PartiallyFailingProvider;
+
+        impl MetricsProvider for PartiallyFailingProvider {
+            fn add_metrics(
+                &self,
+                metrics: &mut Vec<MetricFamily>,
+                prefix: Option<&str>,
+                config: &MetricsConfig,
+            ) {
+                collections(vec![]).add_metrics(metrics, prefix, config);
+                add_metrics_catch_unwind(&PanickingProvider, metrics, prefix, config);
+            }
+        }
+
+        let errors_before = METRICS_COLLECTION_ERRORS.load(Ordering::Relaxed);
+
+        let mut builder = MetricsBuilder::new(None, &MetricsConfig::default());
+        builder.update_from(&PartiallyFailingProvider);
+
+        // Other tests may fail providers concurrently, so only a lower bound is known
+        assert!(
+            counter_value(&builder.data.metrics, "metrics_collection_errors_total")
+                >= (errors_before + 1) as f64,
+        );
+    }
+
+    #[test]
+    fn test_metrics_prefix() {
+        let grpc = GrpcTelemetry {
+            responses: HashMap::from([("/qdrant.Points/Search".to_string(), Default::default())]),
+            responses_by_code: HashMap::new(),
+        };
+
+        for prefix in ["qdrant", "qdrant_"] {
+            let mut metrics = vec![];
+            collections(vec![]).add_metrics(&mut metrics, Some(prefix), &MetricsConfig::default());
+            grpc.add_metrics(&mut metrics, Some(prefix), &MetricsConfig::default());
+
+            find_family(&metrics, "qdrant_collections_total");
+            find_family(&metrics, "qdrant_grpc_responses_total");
+        }
+    }
+
+    #[test]
+    fn test_collection_deleted_vectors() {
+        let mut info = segment_info();
+        info.vector_data = HashMap::from([(
+            String::new(),
+            VectorDataInfo {
+                num_vectors: 900,
+                num_indexed_vectors: 0,
+                num_deleted_vectors: 100,
+            },
+        )]);
+        let mut local = local_shard();
+        local.segments = Some(vec![segment(info)]);
+        let telemetry = collections(vec![collection("compactable", vec![replica_set(0, local)])]);
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+
+        assert_eq!(gauge_value(&metrics, "collection_deleted_vectors"), 100.0);
+        assert_eq!(gauge_value(&metrics, "collection_deleted_ratio"), 0.1);
+    }
+
+    #[test]
+    fn test_cluster_leader() {
+        let telemetry = ClusterStatusTelemetry {
+            number_of_peers: 3,
+            term: 2,
+            commit: 10,
+            pending_operations: 0,
+            role: None,
+            is_voter: true,
+            peer_id: Some(1),
+            leader: Some(2),
+            consensus_thread_status: ConsensusThreadStatus::Stopped,
+        };
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+
+        let leader = find_family(&metrics, "cluster_leader");
+        assert_eq!(leader.get_metric().len(), 1);
+        assert_eq!(leader.get_metric()[0].get_label()[0].name(), "peer_id");
+        assert_eq!(leader.get_metric()[0].get_label()[0].value(), "2");
+        assert_eq!(leader.get_metric()[0].get_gauge().get_value(), 1.0);
+    }
+
+    #[expect(clippy::disallowed_types, reason = "#[sealed_test] uses std::fs::File")]
+    #[sealed_test(env = [("QDRANT_TEST_POD_NAME", "qdrant-0")])]
+    fn test_env_const_labels() {
+        let config = MetricsConfig {
+            env_labels: HashMap::from([
+                ("pod".to_string(), "QDRANT_TEST_POD_NAME".to_string()),
+                (
+                    "node".to_string(),
+                    "QDRANT_TEST_MISSING_NODE_NAME".to_string(),
+                ),
+            ]),
+            ..Default::default()
+        };
+
+        let mut builder = MetricsBuilder::new(None, &config);
+        builder.update_from(&collections(vec![]));
+
+        let collections_total = find_family(&builder.data.metrics, "collections_total");
+        let labels = collections_total.get_metric()[0].get_label();
+        assert_eq!(labels.len(), 1);
+        assert_eq!(labels[0].name(), "pod");
+        assert_eq!(labels[0].value(), "qdrant-0");
+    }
+
+    #[test]
+    fn test_collection_segments_optimizing() {
+        let tracker = |segment_ids: Vec<_>, status| TrackerTelemetry {
+            name: "merge".to_string(),
+            segment_ids,
+            status,
+            start_at: Utc::now(),
+            end_at: None,
+        };
+        let mut local = local_shard();
+        local.optimizations.log = Some(vec![
+            tracker(vec![1, 2, 3], TrackerStatus::Optimizing),
+            tracker(vec![4, 5], TrackerStatus::Optimizing),
+            tracker(vec![6, 7], TrackerStatus::Done),
+        ]);
+        let telemetry = collections(vec![collection("optimizing", vec![replica_set(0, local)])]);
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+
+        assert_eq!(gauge_value(&metrics, "collection_segments_optimizing"), 5.0);
+    }
+
+    #[test]
+    fn test_metrics_encoded_size() {
+        let telemetry = collections(vec![
+            collection("first", vec![replica_set(0, local_shard())]),
+            collection("second", vec![replica_set(0, local_shard())]),
+        ]);
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+        let metrics_data = MetricsData::from(metrics);
+        let encoded_size = metrics_data.encoded_size();
+        assert_eq!(encoded_size, metrics_data.format_metrics().len());
+
+        // Only reported if enabled, as it encodes the metrics once more
+        let mut builder = MetricsBuilder::new(None, &MetricsConfig::default());
+        builder.update_from(&telemetry);
+        assert!(builder.data.family("metrics_payload_bytes").is_none());
+
+        let config = MetricsConfig {
+            payload_size: true,
+            ..Default::default()
+        };
+        let mut builder = MetricsBuilder::new(None, &config);
+        builder.update_from(&telemetry);
+        let payload_bytes = gauge_value(&builder.data.metrics, "metrics_payload_bytes");
+
+        // Size of everything else, which includes the collection errors counter of the builder
+        let mut others = builder.data.metrics.to_vec();
+        others.retain(|family| family.name() != "metrics_payload_bytes");
+        assert_eq!(
+            payload_bytes,
+            MetricsData::from(others).encoded_size() as f64,
+        );
+        assert!(payload_bytes > encoded_size as f64);
+    }
+
+    #[test]
+    fn test_aggregate_collection_metrics() {
+        let with_points = |id: &str, points: usize| {
+            let mut local = local_shard();
+            local.num_points = Some(points);
+            local.num_vectors = Some(points * 2);
+            collection(id, vec![replica_set(0, local)])
+        };
+        let telemetry = collections(vec![with_points("first", 10), with_points("second", 20)]);
+        let config = MetricsConfig {
+            aggregate_collection_metrics: true,
+            ..Default::default()
+        };
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &config);
+
+        assert_eq!(gauge_value(&metrics, "points_total"), 30.0);
+        assert_eq!(gauge_value(&metrics, "vectors_total"), 60.0);
+        assert_eq!(
+            find_family(&metrics, "collection_points")
+                .get_metric()
+                .len(),
+            2,
+        );
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+        assert!(!metrics.iter().any(|family| family.name() == "points_total"));
+    }
+
+    #[test]
+    fn test_app_feature_enabled() {
+        let telemetry = AppFeaturesTelemetry {
+            debug: false,
+            service_debug_feature: false,
+            recovery_mode: true,
+            gpu: true,
+            rocksdb: false,
+        };
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+
+        let features = find_family(&metrics, "app_feature_enabled")
+            .get_metric()
+            .iter()
+            .map(|metric| {
+                (
+                    metric.get_label()[0].value().to_string(),
+                    metric.get_gauge().get_value(),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        assert_eq!(features.len(), 5);
+        assert_eq!(features["recovery_mode"], 1.0);
+        assert_eq!(features["gpu"], 1.0);
+        assert_eq!(features["rocksdb"], 0.0);
+    }
+
+    #[test]
+    fn test_compact_endpoint_labels() {
+        let telemetry = WebApiTelemetry {
+            responses: HashMap::from([(
+                "POST /collections/{name}/points/search/matrix/offsets".to_string(),
+                HashMap::from([(200, Default::default())]),
+            )]),
+        };
+
+        let endpoint_label = |metrics: &[MetricFamily]| {
+            find_family(metrics, "rest_responses_total").get_metric()[0]
+                .get_label()
+                .iter()
+                .find(|label| label.name() == "endpoint")
+                .unwrap()
+                .value()
+                .to_string()
+        };
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+        assert_eq!(
+            endpoint_label(&metrics),
+            "/collections/{name}/points/search/matrix/offsets",
+        );
+        assert!(
+            !metrics
+                .iter()
+                .any(|family| family.name() == "rest_endpoint_info")
+        );
+
+        let config = MetricsConfig {
+            compact_endpoint_labels: true,
+            ..Default::default()
+        };
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &config);
+        assert_eq!(endpoint_label(&metrics), "search_matrix_offsets");
+        find_family(&metrics, "rest_endpoint_info");
+    }
+
+    #[test]
+    fn test_collection_serving_replicas() {
+        let with_states = |id: ShardId, states: &[ReplicaState]| {
+            let mut shard = replica_set(id, local_shard());
+            shard.replicate_states = states
+                .iter()
+                .enumerate()
+                .map(|(peer_id, state)| (peer_id as u64, *state))
+                .collect();
+            shard
+        };
+        let telemetry = collections(vec![collection(
+            "replicated",
+            vec![
+                with_states(
+                    0,
+                    &[
+                        ReplicaState::Active,
+                        ReplicaState::Active,
+                        ReplicaState::ReshardingScaleDown,
+                        ReplicaState::Dead,
+                    ],
+                ),
+                with_states(1, &[ReplicaState::Active, ReplicaState::Active]),
+            ],
+        )]);
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+
+        assert_eq!(gauge_value(&metrics, "collection_serving_replicas"), 5.0);
+    }
+
+    #[test]
+    fn test_duration_unit_milliseconds() {
+        let stats = OperationDurationStatistics {
+            count: 3,
+            avg_duration_micros: Some(2000.0),
+            total_duration_micros: Some(6000),
+            duration_micros_histogram: vec![(1000.0, 1), (5000.0, 3)],
+            ..Default::default()
+        };
+        let telemetry = GrpcTelemetry {
+            responses: HashMap::from([("/qdrant.Points/Search".to_string(), stats)]),
+            responses_by_code: HashMap::new(),
+        };
+        let config = MetricsConfig {
+            duration_unit: MetricsDurationUnit::Milliseconds,
+            ..Default::default()
+        };
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &config);
+
+        assert_eq!(
+            gauge_value(&metrics, "grpc_responses_avg_duration_milliseconds"),
+            2.0,
+        );
+        let histogram = find_family(&metrics, "grpc_responses_duration_milliseconds").get_metric()
+            [0]
+        .get_histogram();
+        assert_eq!(histogram.get_sample_sum(), 6.0);
+        let upper_bounds = histogram
+            .get_bucket()
+            .iter()
+            .map(|bucket| bucket.upper_bound())
+            .collect::<Vec<_>>();
+        assert_eq!(upper_bounds, vec![1.0, 5.0]);
+    }
+
+    #[test]
+    fn test_collection_recent_optimizer_failures() {
+        let tracker = |status| TrackerTelemetry {
+            name: "indexing".to_string(),
+            segment_ids: vec![1],
+            status,
+            start_at: Utc::now(),
+            end_at: Some(Utc::now()),
+        };
+        let mut local = local_shard();
+        local.optimizations.log = Some(vec![
+            tracker(TrackerStatus::Error("No space left on device".to_string())),
+            tracker(TrackerStatus::Done),
+            tracker(TrackerStatus::Error("No space left on device".to_string())),
+            tracker(TrackerStatus::Cancelled("Collection deleted".to_string())),
+            tracker(TrackerStatus::Error("No space left on device".to_string())),
+        ]);
+        let telemetry = collections(vec![collection("failing", vec![replica_set(0, local)])]);
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+
+        let failures = find_family(&metrics, "collection_recent_optimizer_failures");
+        assert_eq!(failures.get_field_type(), MetricType::GAUGE);
+        assert_eq!(failures.get_metric()[0].get_gauge().get_value(), 3.0);
+    }
+
+    #[test]
+    fn test_collection_vector_datatype() {
+        let mut collection = collection("mixed", vec![]);
+        collection.config.params.vectors = VectorsConfig::Multi(
+            [
+                (
+                    "half".to_string(),
+                    VectorParamsBuilder::new(384, Distance::Cosine)
+                        .with_datatype(Datatype::Float16)
+                        .build(),
+                ),
+                (
+                    "byte".to_string(),
+                    VectorParamsBuilder::new(384, Distance::Dot)
+                        .with_datatype(Datatype::Uint8)
+                        .build(),
+                ),
+            ]
+            .into(),
+        );
+        let telemetry = collections(vec![collection]);
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+
+        let datatypes = find_family(&metrics, "collection_vector_datatype")
+            .get_metric()
+            .iter()
+            .map(|metric| {
+                (
+                    metric.get_label()[1].value().to_string(),
+                    metric.get_label()[2].value().to_string(),
+                )
+            })
+            .collect::<HashMap<_, _>>();
+        assert_eq!(datatypes.len(), 2);
+        assert_eq!(datatypes["half"], "float16");
+        assert_eq!(datatypes["byte"], "uint8");
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_procfs_scrape_interval() {
+        use std::sync::atomic::AtomicUsize;
+
+        use super::{ProcFsMetrics, WithProcFs};
+
+        static COLLECT_CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        fn counting_collect() -> Result<ProcFsMetrics, procfs::ProcError> {
+            COLLECT_CALLS.fetch_add(1, Ordering::Relaxed);
+            ProcFsMetrics::collect()
+        }
+
+        let config = MetricsConfig {
+            procfs_scrape_interval: 3,
+            ..Default::default()
+        };
+        let mut builder = MetricsBuilder::new(None, &config);
+        builder.procfs.collect = counting_collect;
+
+        let telemetry = collections(vec![]);
+        let mut uptimes = vec![];
+        for _ in 0..3 {
+            let procfs = builder.procfs.get();
+            builder.update_from(&WithProcFs {
+                provider: &telemetry,
+                procfs,
+                cgroup: None,
+            });
+            assert!(gauge_value(&builder.data.metrics, "procfs_threads") >= 1.0);
+            uptimes.push(gauge_value(&builder.data.metrics, "procfs_uptime_seconds"));
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(COLLECT_CALLS.load(Ordering::Relaxed), 1);
+
+        // Cheap metrics such as the uptime are still collected on every scrape
+        assert!(uptimes.is_sorted_by(|a, b| a < b), "{uptimes:?}");
+
+        builder.procfs.get();
+        assert_eq!(COLLECT_CALLS.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn test_collection_target_segments() {
+        let mut local = local_shard();
+        local.segments = Some((0..9).map(|_| segment(segment_info())).collect());
+        let mut collection = collection("fragmented", vec![replica_set(0, local)]);
+        collection.config.optimizer_config.default_segment_number = 4;
+        let telemetry = collections(vec![collection]);
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+
+        assert_eq!(gauge_value(&metrics, "collection_segments"), 9.0);
+        assert_eq!(gauge_value(&metrics, "collection_target_segments"), 4.0);
+    }
+
+    #[test]
+    fn test_format_metrics_trailing_newline() {
+        let telemetry = collections(vec![collection(
+            "docs",
+            vec![replica_set(0, local_shard())],
+        )]);
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+        let output = MetricsData::from(metrics).format_metrics();
+        assert!(output.ends_with('\n'));
+        assert!(!output.ends_with("\n\n"));
+
+        assert_eq!(MetricsData::default().format_metrics(), "");
+    }
+
+    #[test]
+    fn test_collection_indexed_points() {
+        let mut info = segment_info();
+        info.num_points = 1000;
+        info.vector_data = HashMap::from([
+            (
+                "text".to_string(),
+                VectorDataInfo {
+                    num_vectors: 1000,
+                    num_indexed_vectors: 1000,
+                    num_deleted_vectors: 0,
+                },
+            ),
+            (
+                "image".to_string(),
+                VectorDataInfo {
+                    num_vectors: 1000,
+                    num_indexed_vectors: 900,
+                    num_deleted_vectors: 0,
+                },
+            ),
+        ]);
+        let mut local = local_shard();
+        local.segments = Some(vec![segment(info)]);
+        let telemetry = collections(vec![collection("uploaded", vec![replica_set(0, local)])]);
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+
+        assert_eq!(gauge_value(&metrics, "collection_indexed_points"), 900.0);
+    }
+
+    #[test]
+    fn test_sort_output() {
+        let format_sorted = || {
+            let mut local = local_shard();
+            local.num_vectors_by_name = Some(
+                ["text", "image", "audio", "code", "title"]
+                    .into_iter()
+                    .map(|name| (name.to_string(), 10))
+                    .collect(),
+            );
+            let telemetry = collections(vec![
+                collection("second", vec![replica_set(0, local.clone())]),
+                collection("first", vec![replica_set(0, local)]),
+            ]);
+
+            let config = MetricsConfig {
+                sort_output: true,
+                ..Default::default()
+            };
+            let mut builder = MetricsBuilder::new(None, &config);
+            builder.update_from(&telemetry);
+            builder.snapshot()
+        };
+
+        let first = format_sorted();
+        let second = format_sorted();
+        assert_eq!(first.format_metrics(), second.format_metrics());
+
+        let names = first
+            .metrics
+            .iter()
+            .map(|family| family.name())
+            .collect::<Vec<_>>();
+        assert!(names.is_sorted());
+    }
+
+    #[test]
+    fn test_preregister_endpoints() {
+        let grpc = GrpcTelemetry {
+            responses: HashMap::new(),
+            responses_by_code: HashMap::new(),
+        };
+        let config = MetricsConfig {
+            preregister_endpoints: true,
+            ..Default::default()
+        };
+
+        let mut metrics = vec![];
+        grpc.add_metrics(&mut metrics, None, &config);
+
+        let responses_total = find_family(&metrics, "grpc_responses_total");
+        let mut endpoints = responses_total
+            .get_metric()
+            .iter()
+            .map(|metric| {
+                assert_eq!(metric.get_counter().get_value(), 0.0);
+                metric.get_label()[0].value().to_string()
+            })
+            .collect::<Vec<_>>();
+        endpoints.sort();
+        assert_eq!(endpoints, super::GRPC_ENDPOINT_WHITELIST);
+
+        let mut metrics = vec![];
+        grpc.add_metrics(&mut metrics, None, &MetricsConfig::default());
+        assert!(
+            metrics
+                .iter()
+                .all(|family| family.name() != "grpc_responses_total")
+        );
+    }
+
+    #[test]
+    fn test_collection_shards_total() {
+        let shards = (0..4).map(|id| replica_set(id, local_shard())).collect();
+        let mut unknown = collection("unknown", vec![]);
+        unknown.shards = None;
+        let telemetry = collections(vec![collection("sharded", shards), unknown]);
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+
+        let shards_total = find_family(&metrics, "collection_shards_total");
+        assert_eq!(shards_total.get_metric().len(), 1);
+        assert_eq!(gauge_value(&metrics, "collection_shards_total"), 4.0);
+    }
+
+    #[test]
+    fn test_app_build_info() {
+        let telemetry = AppBuildTelemetry {
+            name: "qdrant".to_string(),
+            version: "1.0.0".to_string(),
+            features: None,
+            runtime_features: None,
+            hnsw_global_config: None,
+            system: None,
+            jwt_rbac: None,
+            hide_jwt_dashboard: None,
+            startup: Utc::now(),
+        };
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+
+        let build_info = find_family(&metrics, "app_build_info");
+        let target = build_info.get_metric()[0]
+            .get_label()
+            .iter()
+            .find(|label| label.name() == "target")
+            .unwrap();
+        assert!(!target.value().is_empty());
+    }
+
+    #[test]
+    fn test_gauge_precision() {
+        let mut shard = replica_set(0, local_shard());
+        shard.replicate_states = HashMap::from([
+            (1, ReplicaState::Active),
+            (2, ReplicaState::Active),
+            (3, ReplicaState::Dead),
+        ]);
+        let mut collection = collection("under_replicated", vec![shard]);
+        collection.config.params.replication_factor = NonZeroU32::new(3).unwrap();
+        let telemetry = collections(vec![collection]);
+
+        let config = MetricsConfig {
+            gauge_precision: Some(3),
+            ..Default::default()
+        };
+        let mut builder = MetricsBuilder::new(None, &config);
+        builder.update_from(&telemetry);
+
+        let output = builder.snapshot().format_metrics();
+        assert!(
+            output.contains("collection_replication_health_ratio{id=\"under_replicated\"} 0.667\n")
+        );
+        assert_eq!(
+            gauge_value(&builder.data.metrics, "collection_replication_health_ratio"),
+            0.667,
+        );
+    }
+
+    #[test]
+    fn test_gauge_precision_keeps_timestamps() {
+        let startup = chrono::DateTime::from_timestamp(1_760_000_123, 0).unwrap();
+        let telemetry = AppBuildTelemetry {
+            name: "qdrant".to_string(),
+            version: "1.0.0".to_string(),
+            features: None,
+            runtime_features: None,
+            hnsw_global_config: None,
+            system: None,
+            jwt_rbac: None,
+            hide_jwt_dashboard: None,
+            startup,
+        };
+        let config = MetricsConfig {
+            gauge_precision: Some(3),
+            restart_epoch: true,
+            ..Default::default()
+        };
+        let mut builder = MetricsBuilder::new(None, &config);
+        builder.update_from(&telemetry);
+
+        // Rounding would make restarts within the same ~10 million seconds indistinguishable
+        assert_eq!(
+            gauge_value(&builder.data.metrics, "process_restart_epoch"),
+            1_760_000_123.0,
+        );
+    }
+
+    #[test]
+    fn test_write_textfile() {
+        let telemetry = collections(vec![collection(
+            "docs",
+            vec![replica_set(0, local_shard())],
+        )]);
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+        let metrics_data = MetricsData::from(metrics);
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("qdrant.prom");
+        metrics_data.write_textfile(&path).unwrap();
+
+        assert_eq!(
+            fs_err::read_to_string(&path).unwrap(),
+            metrics_data.format_metrics(),
+        );
+        let files = fs_err::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect::<Vec<_>>();
+        assert_eq!(files, vec!["qdrant.prom"]);
+    }
+
+    #[test]
+    fn test_collection_segment_sizes() {
+        const MB: usize = 1024 * 1024;
+
+        let with_size = |disk_usage_bytes| {
+            let mut info = segment_info();
+            info.disk_usage_bytes = disk_usage_bytes;
+            segment(info)
+        };
+        let mut local = local_shard();
+        local.segments = Some(vec![with_size(MB), with_size(MB), with_size(100 * MB)]);
+        let telemetry = collections(vec![collection("uneven", vec![replica_set(0, local)])]);
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+
+        assert_eq!(
+            gauge_value(&metrics, "collection_segment_size_min_bytes"),
+            MB as f64,
+        );
+        assert_eq!(
+            gauge_value(&metrics, "collection_segment_size_max_bytes"),
+            (100 * MB) as f64,
+        );
+        assert_eq!(
+            gauge_value(&metrics, "collection_segment_size_avg_bytes"),
+            (34 * MB) as f64,
+        );
+    }
+
+    #[test]
+    fn test_response_rates() {
+        let grpc = |count| GrpcTelemetry {
+            responses: HashMap::from([(
+                "/qdrant.Points/Search".to_string(),
+                OperationDurationStatistics {
+                    count,
+                    ..Default::default()
+                },
+            )]),
+            responses_by_code: HashMap::new(),
+        };
+        let config = MetricsConfig {
+            response_rates: true,
+            ..Default::default()
+        };
+        let mut builder = MetricsBuilder::new(None, &config);
+
+        let first_scrape = Instant::now();
+        builder.update_from_at(&grpc(50), first_scrape);
+        assert!(
+            builder
+                .data
+                .metrics
+                .iter()
+                .all(|family| family.name() != "grpc_responses_per_second")
+        );
+
+        builder.update_from_at(&grpc(150), first_scrape + Duration::from_secs(10));
+        assert_eq!(
+            gauge_value(&builder.data.metrics, "grpc_responses_per_second"),
+            10.0,
+        );
+    }
+
+    #[test]
+    fn test_response_rates_window() {
+        let grpc = |count| GrpcTelemetry {
+            responses: HashMap::from([(
+                "/qdrant.Points/Search".to_string(),
+                OperationDurationStatistics {
+                    count,
+                    ..Default::default()
+                },
+            )]),
+            responses_by_code: HashMap::new(),
+        };
+        let config = MetricsConfig {
+            response_rates: true,
+            response_rates_window_sec: Some(60),
+            ..Default::default()
+        };
+        let mut builder = MetricsBuilder::new(None, &config);
+
+        // Two scrapers, 5 seconds apart
+        let start = Instant::now();
+        for (secs, count) in [(0, 0), (5, 100), (30, 100), (35, 100), (60, 100)] {
+            builder.update_from_at(&grpc(count), start + Duration::from_secs(secs));
+        }
+
+        // Over the window since the scrape at 5 seconds, not the 5 seconds since the other scraper
+        builder.update_from_at(&grpc(700), start + Duration::from_secs(65));
+        assert_eq!(
+            gauge_value(&builder.data.metrics, "grpc_responses_per_second"),
+            10.0,
+        );
+    }
+
+    #[test]
+    fn test_format_family() {
+        let telemetry = collections(vec![collection(
+            "docs",
+            vec![replica_set(0, local_shard())],
+        )]);
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+        let metrics_data = MetricsData::from(metrics);
+
+        assert_eq!(
+            metrics_data.family("collections_total").unwrap().name(),
+            "collections_total",
+        );
+        assert_eq!(
+            metrics_data.format_family("collections_total").unwrap(),
+            "# HELP collections_total number of collections\n\
+             # TYPE collections_total gauge\n\
+             collections_total 1\n",
+        );
+        assert!(metrics_data.format_family("unknown").is_none());
+    }
+
+    #[test]
+    fn test_rest_prefix() {
+        let telemetry = WebApiTelemetry {
+            responses: HashMap::from([(
+                "POST /collections/{name}/points/search".to_string(),
+                HashMap::from([(200, Default::default())]),
+            )]),
+        };
+        let config = MetricsConfig {
+            rest_prefix: Some("http".to_string()),
+            ..Default::default()
+        };
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, Some("qdrant"), &config);
+
+        find_family(&metrics, "qdrant_http_responses_total");
+        assert!(
+            metrics
+                .iter()
+                .all(|family| !family.name().starts_with("qdrant_rest_"))
+        );
+    }
+
+    #[test]
+    fn test_collection_vector_coverage_ratio() {
+        let mut local = local_shard();
+        local.num_points = Some(1000);
+        local.num_vectors_by_name = Some(HashMap::from([("image".to_string(), 500)]));
+        let telemetry = collections(vec![collection("partial", vec![replica_set(0, local)])]);
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+
+        assert_eq!(
+            gauge_value(&metrics, "collection_vector_coverage_ratio"),
+            0.5,
+        );
+    }
+
+    #[test]
+    fn test_app_log_level() {
+        let metrics =
+            MetricsData::from(vec![super::log_level_family(log::LevelFilter::Info, None)]);
+
+        assert!(
+            metrics
+                .format_metrics()
+                .contains("app_log_level{level=\"info\"} 1\n")
+        );
+    }
+
+    #[test]
+    fn test_storage_disk_metrics() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut builder =
+            MetricsBuilder::new(None, &MetricsConfig::default()).with_storage_path(dir.path());
+        builder.update_from(&collections(vec![]));
+
+        let free = gauge_value(&builder.data.metrics, "storage_disk_free_bytes");
+        let total = gauge_value(&builder.data.metrics, "storage_disk_total_bytes");
+        assert!(free > 0.0);
+        assert!(total > 0.0);
+        assert!(free <= total);
+    }
+
+    #[test]
+    fn test_format_graphite() {
+        let metrics = vec![
+            super::metric_family(
+                "collection_points",
+                "approximate amount of points per collection",
+                MetricType::GAUGE,
+                vec![super::gauge(42.0, &[("id", "docs")])],
+                Some("qdrant"),
+            ),
+            super::metric_family(
+                "rest_responses_total",
+                "total number of responses",
+                MetricType::COUNTER,
+                vec![super::counter(
+                    7.0,
+                    &[("method", "GET"), ("endpoint", "/collections")],
+                )],
+                Some("qdrant"),
+            ),
+        ];
+        let with_prefix = |prefix: &str| MetricsData {
+            prefix: Some(prefix.to_string()),
+            ..MetricsData::from(metrics.clone())
+        };
+
+        assert_eq!(
+            with_prefix("qdrant").format_graphite(1_700_000_000),
+            "qdrant.collection_points;id=docs 42 1700000000\n\
+             qdrant.rest_responses_total;method=GET;endpoint=/collections 7 1700000000\n",
+        );
+        assert_eq!(
+            with_prefix("qdrant_").format_graphite(1_700_000_000),
+            with_prefix("qdrant").format_graphite(1_700_000_000),
+        );
+    }
+
+    #[test]
+    fn test_format_graphite_histogram() {
+        let grpc = GrpcTelemetry {
+            responses: HashMap::from([(
+                "/qdrant.Points/Search".to_string(),
+                OperationDurationStatistics {
+                    count: 2,
+                    duration_micros_histogram: vec![(1000.0, 1)],
+                    ..Default::default()
+                },
+            )]),
+            responses_by_code: HashMap::new(),
+        };
+        let mut builder = MetricsBuilder::new(Some("qdrant"), &MetricsConfig::default());
+        builder.update_from(&grpc);
+        let output = builder.snapshot().format_graphite(0);
+
+        // All path components are separated by dots, tags by semicolons
+        let histogram_paths = output
+            .lines()
+            .filter_map(|line| line.split(';').next())
+            .filter(|path| path.starts_with("qdrant.grpc_responses_duration_seconds"))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            histogram_paths,
+            vec![
+                "qdrant.grpc_responses_duration_seconds.bucket",
+                "qdrant.grpc_responses_duration_seconds.bucket",
+                "qdrant.grpc_responses_duration_seconds.sum",
+                "qdrant.grpc_responses_duration_seconds.count",
+            ],
+        );
+        assert!(output.lines().all(|line| line.starts_with("qdrant.")));
+    }
+
+    #[test]
+    fn test_large_collections_total() {
+        let with_points = |id: &str, points: usize| {
+            let mut local = local_shard();
+            local.num_points = Some(points);
+            collection(id, vec![replica_set(0, local)])
+        };
+        let telemetry = collections(vec![
+            with_points("small", 5_000_000),
+            with_points("large", 15_000_000),
+            with_points("huge", 20_000_000),
+        ]);
+        let config = MetricsConfig {
+            large_collection_points_threshold: Some(10_000_000),
+            ..Default::default()
+        };
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &config);
+
+        assert_eq!(gauge_value(&metrics, "large_collections_total"), 2.0);
+    }
+
+    #[test]
+    fn test_shard_transfers_active() {
+        let transfer = |shard_id| ShardTransferInfo {
+            shard_id,
+            to_shard_id: None,
+            from: 1,
+            to: 2,
+            sync: false,
+            method: None,
+            comment: None,
+        };
+        let mut first = collection("first", vec![replica_set(0, local_shard())]);
+        first.transfers = Some(vec![transfer(0), transfer(1)]);
+        let mut second = collection("second", vec![replica_set(0, local_shard())]);
+        second.transfers = Some(vec![transfer(0)]);
+        let telemetry = collections(vec![first, second]);
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+
+        assert_eq!(gauge_value(&metrics, "shard_transfers_active"), 3.0);
+    }
+
+    #[test]
+    fn test_skip_duration_histograms() {
+        let grpc = GrpcTelemetry {
+            responses: HashMap::from([(
+                "/qdrant.Points/Search".to_string(),
+                OperationDurationStatistics {
+                    count: 1,
+                    avg_duration_micros: Some(1000.0),
+                    ..Default::default()
+                },
+            )]),
+            responses_by_code: HashMap::new(),
+        };
+        let config = MetricsConfig {
+            skip_duration_histograms: true,
+            ..Default::default()
+        };
+
+        let mut metrics = vec![];
+        grpc.add_metrics(&mut metrics, None, &config);
+
+        assert!(
+            metrics
+                .iter()
+                .all(|family| family.name() != "grpc_responses_duration_seconds")
+        );
+        assert_eq!(
+            gauge_value(&metrics, "grpc_responses_avg_duration_seconds"),
+            0.001,
+        );
+    }
+
+    #[test]
+    fn test_restart_epoch() {
+        let telemetry = AppBuildTelemetry {
+            name: "qdrant".to_string(),
+            version: "1.0.0".to_string(),
+            features: None,
+            runtime_features: None,
+            hnsw_global_config: None,
+            system: None,
+            jwt_rbac: None,
+            hide_jwt_dashboard: None,
+            startup: Utc::now(),
+        };
+
+        let mut metrics = vec![];
+        telemetry.add_metrics(&mut metrics, None, &MetricsConfig::default());
+        assert!(
+            metrics
+                .iter()
+                .all(|family| family.name() != "process_restart_epoch")
+        );
+
+        let config = MetricsConfig {
+            restart_epoch: true,
+            ..Default::default()
+        };
+        let epochs = (0..2)
+            .map(|_| {
+                let mut metrics = vec![];
+                telemetry.add_metrics(&mut metrics, None, &config);
+                gauge_value(&metrics, "process_restart_epoch")
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(epochs[0], telemetry.startup.timestamp() as f64);
+        assert_eq!(epochs[0], epochs[1]);
+    }
+
+    #[test]
+    fn test_duration_summaries() {
+        let grpc = GrpcTelemetry {
+            responses: HashMap::from([(
+                "/qdrant.Points/Search".to_string(),
+                OperationDurationStatistics {
+                    count: 10,
+                    max_duration_micros: Some(20000.0),
+                    total_duration_micros: Some(50000),
+                    duration_micros_histogram: vec![(1000.0, 5), (10000.0, 9)],
+                    ..Default::default()
+                },
+            )]),
+            responses_by_code: HashMap::new(),
+        };
+        let config = MetricsConfig {
+            duration_summaries: true,
+            ..Default::default()
+        };
+
+        let mut metrics = vec![];
+        grpc.add_metrics(&mut metrics, None, &config);
+
+        assert!(
+            metrics
+                .iter()
+                .all(|family| family.name() != "grpc_responses_duration_seconds")
+        );
+        let family = find_family(&metrics, "grpc_responses_duration_summary_seconds");
+        assert_eq!(family.get_field_type(), MetricType::SUMMARY);
+
+        let summary = family.get_metric()[0].get_summary();
+        assert_eq!(summary.get_sample_count(), 10);
+        assert!((summary.get_sample_sum() - 0.05).abs() < 1e-9);
+
+        let quantiles = summary
+            .get_quantile()
+            .iter()
+            .map(|quantile| (quantile.quantile(), quantile.value()))
+            .collect::<Vec<_>>();
+        let expected = [(0.5, 0.001), (0.9, 0.01), (0.99, 0.019)];
+        assert_eq!(quantiles.len(), expected.len());
+        for ((q, value), (expected_q, expected_value)) in quantiles.into_iter().zip(expected) {
+            assert_eq!(q, expected_q);
+            assert!((value - expected_value).abs() < 1e-9, "{q}: {value}");
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn test_cgroup_metrics() {
+        use super::CgroupMetrics;
+
+        let dir = tempfile::tempdir().unwrap();
+        assert!(CgroupMetrics::collect_from(dir.path()).is_none());
+
+        fs_err::write(dir.path().join("cgroup.controllers"), "cpu memory\n").unwrap();
+        fs_err::write(dir.path().join("memory.max"), "1073741824\n").unwrap();
+        fs_err::write(dir.path().join("memory.current"), "536870912\n").unwrap();
+        fs_err::write(dir.path().join("cpu.max"), "150000 100000\n").unwrap();
+
+        let cgroup = CgroupMetrics::collect_from(dir.path()).unwrap();
+        let mut metrics = vec![];
+        cgroup.add_metrics(&mut metrics, None, &MetricsConfig::default());
+        assert_eq!(
+            gauge_value(&metrics, "cgroup_memory_limit_bytes"),
+            1073741824.0,
+        );
+        assert_eq!(
+            gauge_value(&metrics, "cgroup_memory_current_bytes"),
+            536870912.0,
+        );
+        assert_eq!(gauge_value(&metrics, "cgroup_cpu_quota"), 1.5);
+
+        // Unlimited memory and CPU are not reported
+        fs_err::write(dir.path().join("memory.max"), "max\n").unwrap();
+        fs_err::write(dir.path().join("cpu.max"), "max 100000\n").unwrap();
+
+        let cgroup = CgroupMetrics::collect_from(dir.path()).unwrap();
+        let mut metrics = vec![];
+        cgroup.add_metrics(&mut metrics, None, &MetricsConfig::default());
+        let names = metrics
+            .iter()
+            .map(|family| family.name())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["cgroup_memory_current_bytes"]);
+    }
+
+    #[test]
+    fn test_ndjson_skips_nan_quantiles() {
+        let grpc = GrpcTelemetry {
+            responses: HashMap::from([("/qdrant.Points/Search".to_string(), Default::default())]),
+            responses_by_code: HashMap::new(),
+        };
+        let config = MetricsConfig {
+            duration_summaries: true,
+            ..Default::default()
+        };
+
+        let mut metrics = vec![];
+        grpc.add_metrics(&mut metrics, None, &config);
+        let ndjson = MetricsData::from(metrics).to_ndjson();
+
+        let summary_names = ndjson
+            .lines()
+            .map(|line| serde_json::from_str::<serde_json::Value>(line).unwrap())
+            .filter(|sample| sample["type"] == "summary")
+            .map(|sample| sample["name"].as_str().unwrap().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            summary_names,
+            vec![
+                "grpc_responses_duration_summary_seconds_sum",
+                "grpc_responses_duration_summary_seconds_count",
+            ],
+        );
+    }
+
+    #[test]
+    fn test_duration_summaries_without_histograms() {
+        let grpc = GrpcTelemetry {
+            responses: HashMap::from([(
+                "/qdrant.Points/Search".to_string(),
+                OperationDurationStatistics {
+                    count: 1,
+                    duration_micros_histogram: vec![(1000.0, 1)],
+                    ..Default::default()
+                },
+            )]),
+            responses_by_code: HashMap::new(),
+        };
+        let config = MetricsConfig {
+            skip_duration_histograms: true,
+            duration_summaries: true,
+            ..Default::default()
+        };
+
+        let mut metrics = vec![];
+        grpc.add_metrics(&mut metrics, None, &config);
+
+        assert!(
+            metrics
+                .iter()
+                .all(|family| family.name() != "grpc_responses_duration_seconds")
+        );
+        let family = find_family(&metrics, "grpc_responses_duration_summary_seconds");
+        assert_eq!(family.get_metric()[0].get_summary().get_sample_count(), 1);
+    }
 }
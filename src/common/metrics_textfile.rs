@@ -0,0 +1,81 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+
+use common::types::{DetailsLevel, TelemetryDetail};
+use storage::rbac::Access;
+use tokio::sync::Mutex;
+
+use super::metrics::MetricsBuilder;
+use super::telemetry::TelemetryCollector;
+use crate::settings::MetricsConfig;
+
+const DETAIL: TelemetryDetail = TelemetryDetail {
+    level: DetailsLevel::Level4,
+    histograms: true,
+};
+const DEFAULT_WRITE_INTERVAL: Duration = Duration::from_secs(15);
+
+const FULL_ACCESS: Access = Access::full("Metrics textfile writer");
+
+/// Periodically writes metrics to a file, for the node exporter textfile collector.
+pub struct MetricsTextfileWriter {
+    path: PathBuf,
+    interval: Duration,
+    telemetry: Arc<Mutex<TelemetryCollector>>,
+    metrics_builder: MetricsBuilder,
+}
+
+impl MetricsTextfileWriter {
+    fn new(
+        path: PathBuf,
+        telemetry: Arc<Mutex<TelemetryCollector>>,
+        prefix: Option<&str>,
+        config: &MetricsConfig,
+        storage_path: &str,
+    ) -> Self {
+        let interval = config
+            .textfile_interval_sec
+            .map_or(DEFAULT_WRITE_INTERVAL, Duration::from_secs);
+
+        Self {
+            path,
+            interval,
+            telemetry,
+            metrics_builder: MetricsBuilder::new(prefix, config).with_storage_path(storage_path),
+        }
+    }
+
+    async fn write(&mut self) {
+        let data = self
+            .telemetry
+            .lock()
+            .await
+            .prepare_data(&FULL_ACCESS, DETAIL)
+            .await;
+        self.metrics_builder.update(&data);
+
+        let path = self.path.clone();
+        let metrics_data = self.metrics_builder.snapshot();
+        let result = tokio::task::spawn_blocking(move || metrics_data.write_textfile(&path)).await;
+        match result {
+            Ok(Ok(())) => {}
+            Ok(Err(err)) => log::warn!("Failed to write metrics textfile: {err}"),
+            Err(err) => log::warn!("Failed to write metrics textfile: {err}"),
+        }
+    }
+
+    pub async fn run(
+        path: PathBuf,
+        telemetry: Arc<Mutex<TelemetryCollector>>,
+        prefix: Option<String>,
+        config: MetricsConfig,
+        storage_path: String,
+    ) {
+        let mut writer = Self::new(path, telemetry, prefix.as_deref(), &config, &storage_path);
+        loop {
+            writer.write().await;
+            tokio::time::sleep(writer.interval).await;
+        }
+    }
+}
@@ -7,6 +7,7 @@ pub mod helpers;
 pub mod http_client;
 pub mod inference;
 pub mod metrics;
+pub mod metrics_textfile;
 pub mod pyroscope_state;
 pub mod query;
 pub mod snapshots;
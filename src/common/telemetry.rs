@@ -33,7 +33,7 @@ pub struct TelemetryCollector {
 #[derive(Serialize, Clone, Debug, JsonSchema, Anonymize)]
 pub struct TelemetryData {
     #[anonymize(false)]
-    id: String,
+    pub(crate) id: String,
     pub(crate) app: AppBuildTelemetry,
     pub(crate) collections: CollectionsTelemetry,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -62,6 +62,8 @@ pub struct ClusterStatusTelemetry {
     pub is_voter: bool,
     #[anonymize(false)]
     pub peer_id: Option<PeerId>,
+    #[anonymize(false)]
+    pub leader: Option<PeerId>,
     pub consensus_thread_status: ConsensusThreadStatus,
 }
 
@@ -107,6 +109,7 @@ impl ClusterTelemetry {
                         role: cluster_info.raft_info.role,
                         is_voter: cluster_info.raft_info.is_voter,
                         peer_id: Some(cluster_info.peer_id),
+                        leader: cluster_info.raft_info.leader,
                         consensus_thread_status: cluster_info.consensus_thread_status,
                     }),
                 })
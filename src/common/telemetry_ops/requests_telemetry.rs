@@ -10,6 +10,7 @@ use segment::common::operation_time_statistics::{
 };
 use serde::Serialize;
 use storage::rbac::{Access, AccessRequirements};
+use tonic::Code;
 
 pub type HttpStatusCode = u16;
 
@@ -22,6 +23,13 @@ pub struct WebApiTelemetry {
 pub struct GrpcTelemetry {
     #[anonymize(with = anonymize_collection_values)]
     pub responses: HashMap<String, OperationDurationStatistics>,
+    /// Number of responses per method, grouped by gRPC status code name.
+    ///
+    /// Responses with the status only sent in the trailers are grouped as
+    /// [`GRPC_CODE_UNKNOWN`], as the trailers are not inspected.
+    #[serde(skip)]
+    #[anonymize(false)]
+    pub responses_by_code: HashMap<String, HashMap<String, usize>>,
 }
 
 pub struct ActixTelemetryCollector {
@@ -40,6 +48,8 @@ pub struct TonicTelemetryCollector {
 #[derive(Default)]
 pub struct TonicWorkerTelemetryCollector {
     methods: HashMap<String, Arc<Mutex<OperationDurationsAggregator>>>,
+    /// Response counts per method and status code, `None` if the status is sent in the trailers
+    codes: HashMap<String, HashMap<Option<Code>, usize>>,
 }
 
 impl ActixTelemetryCollector {
@@ -77,7 +87,18 @@ impl TonicTelemetryCollector {
 }
 
 impl TonicWorkerTelemetryCollector {
-    pub fn add_response(&mut self, method: String, instant: std::time::Instant) {
+    pub fn add_response(
+        &mut self,
+        method: String,
+        code: Option<Code>,
+        instant: std::time::Instant,
+    ) {
+        *self
+            .codes
+            .entry(method.clone())
+            .or_default()
+            .entry(code)
+            .or_default() += 1;
         let aggregator = self
             .methods
             .entry(method)
@@ -90,7 +111,52 @@ impl TonicWorkerTelemetryCollector {
         for (method, aggregator) in self.methods.iter() {
             responses.insert(method.clone(), aggregator.lock().get_statistics(detail));
         }
-        GrpcTelemetry { responses }
+        let responses_by_code = self
+            .codes
+            .iter()
+            .map(|(method, codes)| {
+                let codes = codes
+                    .iter()
+                    .map(|(code, count)| {
+                        let name = code.map_or(GRPC_CODE_UNKNOWN, grpc_code_name);
+                        (name.to_string(), *count)
+                    })
+                    .collect();
+                (method.clone(), codes)
+            })
+            .collect();
+        GrpcTelemetry {
+            responses,
+            responses_by_code,
+        }
+    }
+}
+
+/// Status code name of gRPC responses with the status only sent in the trailers.
+///
+/// Lowercase to not be confused with the `UNKNOWN` status code.
+pub const GRPC_CODE_UNKNOWN: &str = "unknown";
+
+/// Canonical name of a gRPC status code, as used in the gRPC specification.
+fn grpc_code_name(code: Code) -> &'static str {
+    match code {
+        Code::Ok => "OK",
+        Code::Cancelled => "CANCELLED",
+        Code::Unknown => "UNKNOWN",
+        Code::InvalidArgument => "INVALID_ARGUMENT",
+        Code::DeadlineExceeded => "DEADLINE_EXCEEDED",
+        Code::NotFound => "NOT_FOUND",
+        Code::AlreadyExists => "ALREADY_EXISTS",
+        Code::PermissionDenied => "PERMISSION_DENIED",
+        Code::ResourceExhausted => "RESOURCE_EXHAUSTED",
+        Code::FailedPrecondition => "FAILED_PRECONDITION",
+        Code::Aborted => "ABORTED",
+        Code::OutOfRange => "OUT_OF_RANGE",
+        Code::Unimplemented => "UNIMPLEMENTED",
+        Code::Internal => "INTERNAL",
+        Code::Unavailable => "UNAVAILABLE",
+        Code::DataLoss => "DATA_LOSS",
+        Code::Unauthenticated => "UNAUTHENTICATED",
     }
 }
 
@@ -129,6 +195,12 @@ impl GrpcTelemetry {
             let entry = self.responses.entry(method.clone()).or_default();
             *entry = entry.clone() + other_statistics.clone();
         }
+        for (method, codes) in &other.responses_by_code {
+            let codes_map = self.responses_by_code.entry(method.clone()).or_default();
+            for (code, count) in codes {
+                *codes_map.entry(code.clone()).or_default() += count;
+            }
+        }
     }
 }
 
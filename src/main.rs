@@ -48,6 +48,7 @@ use crate::common::helpers::{
     load_tls_client_config,
 };
 use crate::common::inference::service::InferenceService;
+use crate::common::metrics_textfile::MetricsTextfileWriter;
 use crate::common::telemetry::TelemetryCollector;
 use crate::common::telemetry_reporting::TelemetryReporter;
 use crate::greeting::welcome;
@@ -522,6 +523,18 @@ fn main() -> anyhow::Result<()> {
         log::info!("Hardware reporting enabled");
     }
 
+    if let Some(textfile_path) = &settings.service.metrics.textfile_path {
+        log::info!("Writing metrics to {}", textfile_path.display());
+
+        runtime_handle.spawn(MetricsTextfileWriter::run(
+            textfile_path.clone(),
+            telemetry_collector.clone(),
+            settings.service.metrics_prefix.clone(),
+            settings.service.metrics.clone(),
+            settings.storage.storage_path.clone(),
+        ));
+    }
+
     // Setup subscribers to listen for issue-able events
     issues_setup::setup_subscribers(&settings);
     init_requests_profile_collector(runtime_handle.clone());
@@ -1,4 +1,6 @@
 use std::borrow::Cow;
+use std::collections::HashMap;
+use std::path::PathBuf;
 use std::{env, io};
 
 use api::grpc::transport_channel_pool::{
@@ -8,6 +10,7 @@ use collection::operations::validation;
 use collection::shards::shard::PeerId;
 use common::flags::FeatureFlags;
 use config::{Config, ConfigError, Environment, File, FileFormat, Source};
+use regex::Regex;
 use serde::Deserialize;
 use storage::types::StorageConfig;
 use validator::{Validate, ValidationError};
@@ -60,9 +63,18 @@ pub struct ServiceConfig {
     pub hardware_reporting: Option<bool>,
 
     /// Global prefix for metrics.
+    ///
+    /// Joined to metric names with a single underscore, whether or not it ends with one. This is
+    /// a breaking change for prefixes without a trailing underscore: `qdrant` used to be prepended
+    /// as is, turning `app_info` into `qdrantapp_info` instead of `qdrant_app_info`.
     #[serde(default)]
     #[validate(custom(function = validate_metrics_prefix))]
     pub metrics_prefix: Option<String>,
+
+    /// Configuration of the metrics output.
+    #[serde(default)]
+    #[validate(nested)]
+    pub metrics: MetricsConfig,
 }
 
 impl ServiceConfig {
@@ -71,6 +83,133 @@ impl ServiceConfig {
     }
 }
 
+#[derive(Debug, Deserialize, Clone, Default, Validate)]
+#[validate(schema(function = "validate_metrics_config"))]
+pub struct MetricsConfig {
+    /// Only report points excluded from `indexed_only` requests for collections having more
+    /// excluded points than this. If not set, all collections are reported.
+    #[serde(default)]
+    pub indexed_only_excluded_threshold: Option<usize>,
+    /// Memory quota of this node in bytes, reported along with its utilization.
+    #[serde(default)]
+    pub memory_quota_bytes: Option<usize>,
+    /// Report the number of collections having more points than this. If not set, large
+    /// collections are not reported.
+    #[serde(default)]
+    pub large_collection_points_threshold: Option<usize>,
+    /// Regex to derive a group from collection names, reporting the points per group. The first
+    /// capture group is used as group name if there is one, the whole match otherwise.
+    #[serde(default)]
+    #[validate(custom(function = validate_collection_group_pattern))]
+    pub collection_group_pattern: Option<String>,
+    /// Skip hardware counters of collections for which they are zero.
+    #[serde(default)]
+    pub skip_zero_hardware_counters: bool,
+    /// Also report aggregates over all collections of key per-collection metrics, such as the
+    /// total amount of points.
+    #[serde(default)]
+    pub aggregate_collection_metrics: bool,
+    /// Use compact labels for REST endpoints, such as `search_batch`, instead of full endpoint
+    /// templates. The mapping is reported in `rest_endpoint_info`.
+    #[serde(default)]
+    pub compact_endpoint_labels: bool,
+    /// Unit of reported durations, such as response durations.
+    #[serde(default)]
+    pub duration_unit: MetricsDurationUnit,
+    /// Skip response duration histograms, keeping the average, minimum and maximum durations.
+    /// Histograms are by far the largest part of the metrics. Summaries are still reported if
+    /// enabled by `duration_summaries`.
+    #[serde(default)]
+    pub skip_duration_histograms: bool,
+    /// Report response durations as `responses_duration_summary_{unit}` summaries with
+    /// pre-computed quantiles, estimated from the histograms, instead of the histograms themselves.
+    #[serde(default)]
+    pub duration_summaries: bool,
+    /// Count threads and mmaps of the process, the most expensive procfs reads, only every this
+    /// many scrapes, reporting the previously counted values in between. Other procfs metrics are
+    /// collected on every scrape. `0` and `1` count them on every scrape.
+    #[serde(default)]
+    pub procfs_scrape_interval: usize,
+    /// Sort metric families by name and their series by labels, making the output deterministic.
+    #[serde(default)]
+    pub sort_output: bool,
+    /// Report all whitelisted gRPC endpoints from startup, with zero responses until they are used.
+    /// Only applies to gRPC: REST series are also labeled by HTTP method and status, which are not
+    /// known before the first response.
+    #[serde(default)]
+    pub preregister_endpoints: bool,
+    /// Round ratios, rates and durations to this many significant digits. Other gauges, such as
+    /// sizes, counts and timestamps, as well as counters and histograms are always reported
+    /// exactly. If not set, gauges are not rounded.
+    #[serde(default)]
+    pub gauge_precision: Option<u32>,
+    /// Periodically write metrics to this file, in the format of the node exporter textfile
+    /// collector. If not set, metrics are only served by the metrics endpoint.
+    #[serde(default)]
+    pub textfile_path: Option<PathBuf>,
+    /// Interval in seconds for writing metrics to `textfile_path`, 15 seconds by default.
+    #[serde(default)]
+    pub textfile_interval_sec: Option<u64>,
+    /// Also report the rate of responses per second over `response_rates_window_sec`, next to
+    /// the response counters. Meant for dashboards that can't compute rates themselves; prefer
+    /// `rate()` on the counters otherwise.
+    #[serde(default)]
+    pub response_rates: bool,
+    /// Window in seconds to compute `response_rates` over, 60 seconds by default. Rates cover at
+    /// least the window once qdrant has been scraped for that long, independent of the number of
+    /// scrapers.
+    #[serde(default)]
+    pub response_rates_window_sec: Option<u64>,
+    /// Prefix of REST metric families, such as `rest_responses_total`. `rest` by default.
+    /// Must differ from the gRPC prefix.
+    #[serde(default)]
+    #[validate(length(min = 1), custom(function = validate_metrics_prefix))]
+    pub rest_prefix: Option<String>,
+    /// Prefix of gRPC metric families, such as `grpc_responses_total`. `grpc` by default.
+    /// Must differ from the REST prefix.
+    #[serde(default)]
+    #[validate(length(min = 1), custom(function = validate_metrics_prefix))]
+    pub grpc_prefix: Option<String>,
+    /// Report `process_restart_epoch`, the start time of the process. It only changes on restart,
+    /// which helps detecting counter resets.
+    #[serde(default)]
+    pub restart_epoch: bool,
+    /// Report `metrics_payload_bytes`, the size of all other metrics in Prometheus text format.
+    /// Encodes the metrics an additional time on every scrape.
+    #[serde(default)]
+    pub payload_size: bool,
+    /// Labels to attach to all metrics, mapping label names to the environment variables to read
+    /// their values from. Environment variables are read once, missing ones are skipped.
+    #[serde(default)]
+    pub env_labels: HashMap<String, String>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricsDurationUnit {
+    #[default]
+    Seconds,
+    Milliseconds,
+}
+
+impl MetricsDurationUnit {
+    /// Name of the unit, used as suffix of metric names.
+    pub fn name(self) -> &'static str {
+        match self {
+            MetricsDurationUnit::Seconds => "seconds",
+            MetricsDurationUnit::Milliseconds => "milliseconds",
+        }
+    }
+
+    /// Number of microseconds in one unit.
+    pub fn micros_per_unit(self) -> f64 {
+        match self {
+            MetricsDurationUnit::Seconds => 1_000_000.0,
+            MetricsDurationUnit::Milliseconds => 1_000.0,
+        }
+    }
+}
+
 #[derive(Debug, Deserialize, Clone, Default, Validate)]
 pub struct ClusterConfig {
     pub enabled: bool, // disabled by default
@@ -424,9 +563,38 @@ fn validate_metrics_prefix(prefix: &str) -> Result<(), ValidationError> {
         );
     }
 
+    // Trailing '_' are trimmed when joining prefixes, which would leave nothing of this one
+    if prefix.trim_end_matches('_').is_empty() {
+        return Err(ValidationError::new("invalid_metrics_prefix")
+            .with_message(Cow::Borrowed("Metrics prefix must not only consist of '_'")));
+    }
+
+    Ok(())
+}
+
+/// Custom validation function for the metrics configuration as a whole.
+fn validate_metrics_config(config: &MetricsConfig) -> Result<(), ValidationError> {
+    // Equal prefixes would emit the REST and gRPC metric families under the same names. Trailing
+    // '_' are trimmed when joining prefixes, so `grpc_` and `grpc` are equal too.
+    let rest_prefix = config.rest_prefix.as_deref().unwrap_or("rest");
+    let grpc_prefix = config.grpc_prefix.as_deref().unwrap_or("grpc");
+    if rest_prefix.trim_end_matches('_') == grpc_prefix.trim_end_matches('_') {
+        return Err(ValidationError::new("duplicate_metrics_prefix")
+            .with_message(Cow::Borrowed("REST and gRPC metrics prefixes must differ")));
+    }
+
     Ok(())
 }
 
+/// Custom validation function for the collection group pattern of metrics.
+fn validate_collection_group_pattern(pattern: &str) -> Result<(), ValidationError> {
+    Regex::new(pattern).map(|_| ()).map_err(|err| {
+        ValidationError::new("invalid_collection_group_pattern").with_message(Cow::Owned(format!(
+            "Collection group pattern must be a valid regex: {err}"
+        )))
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use std::io::Write;
@@ -491,6 +659,39 @@ mod tests {
         assert!(!config.load_errors.is_empty(), "must have load errors")
     }
 
+    #[test]
+    fn test_metrics_config_validation() {
+        let config = MetricsConfig {
+            collection_group_pattern: Some("^(prod|staging)-".to_string()),
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+
+        let config = MetricsConfig {
+            collection_group_pattern: Some("^(prod".to_string()),
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+
+        let with_prefixes = |rest: Option<&str>, grpc: Option<&str>| MetricsConfig {
+            rest_prefix: rest.map(str::to_string),
+            grpc_prefix: grpc.map(str::to_string),
+            ..Default::default()
+        };
+        assert!(with_prefixes(Some("http"), Some("rpc")).validate().is_ok());
+        assert!(with_prefixes(Some("http-"), None).validate().is_err());
+        assert!(with_prefixes(None, Some("")).validate().is_err());
+        assert!(with_prefixes(Some("api"), Some("api")).validate().is_err());
+        assert!(with_prefixes(Some("grpc"), None).validate().is_err());
+        assert!(
+            with_prefixes(Some("grpc_"), Some("grpc"))
+                .validate()
+                .is_err()
+        );
+        assert!(with_prefixes(Some("http_"), None).validate().is_ok());
+        assert!(with_prefixes(Some("__"), None).validate().is_err());
+    }
+
     #[expect(clippy::disallowed_types, reason = "#[sealed_test] uses std::fs::File")]
     #[sealed_test]
     fn test_custom_config() {
@@ -2,6 +2,8 @@ use std::sync::Arc;
 use std::task::{Context, Poll};
 
 use futures_util::future::BoxFuture;
+use tonic::body::BoxBody;
+use tonic::codegen::http::Response;
 use tower::Service;
 use tower_layer::Layer;
 
@@ -22,7 +24,7 @@ pub struct TonicTelemetryLayer {
 
 impl<S> Service<tonic::codegen::http::Request<tonic::transport::Body>> for TonicTelemetryService<S>
 where
-    S: Service<tonic::codegen::http::Request<tonic::transport::Body>>,
+    S: Service<tonic::codegen::http::Request<tonic::transport::Body>, Response = Response<BoxBody>>,
     S::Future: Send + 'static,
 {
     type Response = S::Response;
@@ -43,7 +45,15 @@ where
         Box::pin(async move {
             let instant = std::time::Instant::now();
             let response = future.await?;
-            telemetry_data.lock().add_response(method_name, instant);
+            // Errors are usually sent as trailers-only responses, having the status in the
+            // headers. Otherwise the status is only sent in the trailers after the body, which
+            // are not inspected here, so the status is unknown. This includes successful
+            // responses and errors raised while streaming.
+            let code =
+                tonic::Status::from_header_map(response.headers()).map(|status| status.code());
+            telemetry_data
+                .lock()
+                .add_response(method_name, code, instant);
             Ok(response)
         })
     }